@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use lists::linkedlist::LinkedList;
+use lists::unrolled::UnrolledList;
+
+const SIZES: [u32; 3] = [1_000, 10_000, 100_000];
+
+// 遍历求和：UnrolledList的主要优势，一个节点里连续放着多个元素
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for size in SIZES {
+        let linked: LinkedList<u32> = (0..size).collect();
+        let unrolled: UnrolledList<u32> = (0..size).collect();
+        group.bench_with_input(BenchmarkId::new("LinkedList", size), &linked, |b, list| {
+            b.iter(|| list.iter().fold(0u32, |acc, x| acc.wrapping_add(*x)))
+        });
+        group.bench_with_input(BenchmarkId::new("UnrolledList", size), &unrolled, |b, list| {
+            b.iter(|| list.iter().fold(0u32, |acc, x| acc.wrapping_add(*x)))
+        });
+    }
+    group.finish();
+}
+
+// 两端push再全部pop
+fn push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_pop");
+    for size in SIZES {
+        group.bench_with_input(BenchmarkId::new("LinkedList", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut list = LinkedList::new();
+                for i in 0..size {
+                    list.push_back(i);
+                    list.push_front(i);
+                }
+                while let Some(x) = list.pop_front() {
+                    black_box(x);
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("UnrolledList", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut list = UnrolledList::new();
+                for i in 0..size {
+                    list.push_back(i);
+                    list.push_front(i);
+                }
+                while let Some(x) = list.pop_front() {
+                    black_box(x);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+// 用cursor走到中间插入和删除
+fn cursor_middle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cursor_middle");
+    for size in SIZES {
+        group.bench_with_input(BenchmarkId::new("LinkedList", size), &size, |b, &size| {
+            let mut list: LinkedList<u32> = (0..size).collect();
+            b.iter(|| {
+                list.insert(size as usize / 2, 0);
+                black_box(list.remove(size as usize / 2));
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("UnrolledList", size), &size, |b, &size| {
+            let mut list: UnrolledList<u32> = (0..size).collect();
+            b.iter(|| {
+                list.insert(size as usize / 2, 0);
+                black_box(list.remove(size as usize / 2));
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, iterate, push_pop, cursor_middle);
+criterion_main!(benches);
@@ -0,0 +1,308 @@
+use std::mem::MaybeUninit;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+
+// Michael-Scott 无锁队列，和fifth::List一样是头出尾进的单链表，但可以被多个线程同时push和pop。
+// head始终指向一个哨兵节点，真正的第一个元素在哨兵的next里；pop成功后next成为新的哨兵。
+// 被弹出的哨兵可能还有其他线程在读，所以用crossbeam-epoch延迟到所有线程都离开当前epoch后再释放。
+pub struct Queue<T> {
+    head: Atomic<Node<T>>,
+    tail: Atomic<Node<T>>,
+}
+
+struct Node<T> {
+    // 哨兵节点的数据已经被取走（或者从来没有初始化过），所以释放节点时不能drop它
+    elem: MaybeUninit<T>,
+    next: Atomic<Node<T>>,
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        let queue = Queue {
+            head: Atomic::null(),
+            tail: Atomic::null(),
+        };
+        let sentinel = Owned::new(Node {
+            elem: MaybeUninit::uninit(),
+            next: Atomic::null(),
+        });
+        // 队列还没有共享给其他线程，不需要真正pin住
+        let guard = unsafe { epoch::unprotected() };
+        let sentinel = sentinel.into_shared(guard);
+        queue.head.store(sentinel, Relaxed);
+        queue.tail.store(sentinel, Relaxed);
+        queue
+    }
+
+    // 在队尾添加元素
+    pub fn push(&self, elem: T) {
+        let guard = &epoch::pin();
+        let new = Owned::new(Node {
+            elem: MaybeUninit::new(elem),
+            next: Atomic::null(),
+        })
+        .into_shared(guard);
+
+        loop {
+            let tail = self.tail.load(Acquire, guard);
+            // tail永远不为空，并且在guard存活期间不会被释放
+            let tail_ref = unsafe { tail.deref() };
+            let next = tail_ref.next.load(Acquire, guard);
+
+            // tail落后了（其他线程已经接上了新节点但还没来得及移动tail），帮它往前移
+            if !next.is_null() {
+                let _ = self.tail.compare_exchange(tail, next, Release, Relaxed, guard);
+                continue;
+            }
+
+            // 把新节点接到最后一个节点后面，成功之后再尝试移动tail，失败也没关系，其他线程会帮忙
+            if tail_ref
+                .next
+                .compare_exchange(Shared::null(), new, Release, Relaxed, guard)
+                .is_ok()
+            {
+                let _ = self.tail.compare_exchange(tail, new, Release, Relaxed, guard);
+                return;
+            }
+        }
+    }
+
+    // 从队头取出元素
+    pub fn pop(&self) -> Option<T> {
+        let guard = &epoch::pin();
+        loop {
+            let head = self.head.load(Acquire, guard);
+            let next = unsafe { head.deref() }.next.load(Acquire, guard);
+            let next_ref = unsafe { next.as_ref() }?;
+
+            if self
+                .head
+                .compare_exchange(head, next, Release, Relaxed, guard)
+                .is_ok()
+            {
+                // 如果tail还指向旧的哨兵，先把它移到next，保证tail不会指向被释放的节点
+                let tail = self.tail.load(Relaxed, guard);
+                if head == tail {
+                    let _ = self.tail.compare_exchange(tail, next, Release, Relaxed, guard);
+                }
+                unsafe {
+                    // 旧的哨兵等所有线程都离开当前epoch后再释放
+                    guard.defer_destroy(head);
+                    // 赢得CAS的线程独占next中的数据，之后next成为新的哨兵
+                    return Some(next_ref.elem.assume_init_read());
+                }
+            }
+        }
+    }
+
+    // 返回队头元素的拷贝
+    // 只支持Copy类型：返回引用或者clone都可能和pop同时发生，pop拿走所有权之后可能修改或释放元素内部的数据，
+    // 而Copy类型没有这样的数据，节点中的数据在节点被释放之前也不会再被写入
+    pub fn peek(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        let guard = &epoch::pin();
+        self.first(guard).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let guard = &epoch::pin();
+        self.first(guard).is_none()
+    }
+
+    fn first<'g>(&self, guard: &'g Guard) -> Option<&'g T> {
+        let head = self.head.load(Acquire, guard);
+        let next = unsafe { head.deref() }.next.load(Acquire, guard);
+        unsafe { next.as_ref().map(|node| node.elem.assume_init_ref()) }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        // &mut self 说明没有其他线程在使用队列，可以直接释放
+        while self.pop().is_some() {}
+        unsafe {
+            let guard = epoch::unprotected();
+            let sentinel = self.head.load(Relaxed, guard);
+            drop(sentinel.into_owned());
+        }
+    }
+}
+
+// 元素会在线程之间转移，所以只要求T: Send
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::Queue;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+
+        // Check empty queue behaves right
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+
+        // Populate queue
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+
+        // Check normal removal
+        assert_eq!(queue.peek(), Some(1));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+
+        // Push some more just to make sure nothing's corrupted
+        queue.push(4);
+        queue.push(5);
+
+        // Check normal removal
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(4));
+
+        // Check exhaustion
+        assert_eq!(queue.pop(), Some(5));
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.peek(), None);
+
+        // Check the exhaustion case fixed the pointer right
+        queue.push(6);
+        queue.push(7);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.pop(), Some(6));
+        assert_eq!(queue.pop(), Some(7));
+        assert_eq!(queue.pop(), None);
+    }
+
+    // 每个元素都只被drop一次
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn drop_remaining() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let queue = Queue::new();
+        for _ in 0..10 {
+            queue.push(DropCounter(drops.clone()));
+        }
+        drop(queue.pop());
+        drop(queue.pop());
+        assert_eq!(drops.load(Ordering::SeqCst), 2);
+
+        // 队列中剩下的元素在队列drop时被drop
+        drop(queue);
+        assert_eq!(drops.load(Ordering::SeqCst), 10);
+    }
+
+    const PRODUCERS: usize = 4;
+    const CONSUMERS: usize = 4;
+    const PER_PRODUCER: usize = 10_000;
+
+    #[test]
+    fn stress_mpmc() {
+        let queue = Arc::new(Queue::new());
+        let barrier = Arc::new(Barrier::new(PRODUCERS + CONSUMERS));
+        let popped = Arc::new(AtomicUsize::new(0));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    for i in 0..PER_PRODUCER {
+                        queue.push((p, i));
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                let barrier = barrier.clone();
+                let popped = popped.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    let mut seen = Vec::new();
+                    // 同一个生产者的元素，每个消费者看到的顺序必须是递增的
+                    let mut last = [None; PRODUCERS];
+                    while popped.load(Ordering::SeqCst) < PRODUCERS * PER_PRODUCER {
+                        if let Some((p, i)) = queue.pop() {
+                            popped.fetch_add(1, Ordering::SeqCst);
+                            assert!(last[p] < Some(i));
+                            last[p] = Some(i);
+                            seen.push((p, i));
+                        } else {
+                            thread::yield_now();
+                        }
+                    }
+                    seen
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut all: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+
+        // 每个元素恰好被取出一次
+        all.sort();
+        let expected: Vec<_> = (0..PRODUCERS)
+            .flat_map(|p| (0..PER_PRODUCER).map(move |i| (p, i)))
+            .collect();
+        assert_eq!(all, expected);
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn stress_drop_count() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let queue = Arc::new(Queue::new());
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let queue = queue.clone();
+                let drops = drops.clone();
+                thread::spawn(move || {
+                    for i in 0..5_000 {
+                        queue.push(DropCounter(drops.clone()));
+                        // 一半的线程多pop一些，一半的线程少pop一些，让队列时空时满
+                        if t % 2 == 0 || i % 3 == 0 {
+                            drop(queue.pop());
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        drop(Arc::try_unwrap(queue).ok().unwrap());
+        assert_eq!(drops.load(Ordering::SeqCst), 4 * 5_000);
+    }
+}
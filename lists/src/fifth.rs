@@ -1,8 +1,11 @@
+use std::fmt::{self, Debug};
+use std::iter::FromIterator;
 use std::ptr;
 
 pub struct List<T> {
     head: Link<T>,
     tail: *mut Node<T>,
+    len: usize,
 }
 
 type Link<T> = *mut Node<T>;
@@ -26,7 +29,8 @@ impl<T> List<T> {
     pub fn new() -> Self {
         List {
             head: ptr::null_mut(),
-            tail: ptr::null_mut()
+            tail: ptr::null_mut(),
+            len: 0,
         }
     }
 
@@ -34,7 +38,7 @@ impl<T> List<T> {
         unsafe {
             // into_raw 消费掉 Box (拿走所有权)，返回一个裸指针
             let new_tail = Box::into_raw(Box::new(Node {
-                elem: elem,
+                elem,
                 next: ptr::null_mut(),
             }));
 
@@ -47,6 +51,20 @@ impl<T> List<T> {
     
             self.tail = new_tail;
         }
+        self.len += 1;
+    }
+
+    // 在队头插入，链表为空时新节点同时也是tail
+    pub fn push_front(&mut self, elem: T) {
+        let new_head = Box::into_raw(Box::new(Node {
+            elem,
+            next: self.head,
+        }));
+        if self.tail.is_null() {
+            self.tail = new_head;
+        }
+        self.head = new_head;
+        self.len += 1;
     }
 
     pub fn pop(&mut self) -> Option<T> {
@@ -60,6 +78,7 @@ impl<T> List<T> {
                 if self.head.is_null() {
                     self.tail = ptr::null_mut();
                 }
+                self.len -= 1;
 
                 Some(head.elem)
             }
@@ -74,7 +93,7 @@ impl<T> List<T> {
         }
     }
 
-    pub fn peek_mut(&self) -> Option<&mut T> {
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
         if !self.head.is_null() {
             Some(unsafe { &mut (*self.head).elem })
         } else {
@@ -82,6 +101,46 @@ impl<T> List<T> {
         }
     }
 
+    pub fn peek_back(&self) -> Option<&T> {
+        unsafe { self.tail.as_ref().map(|node| &node.elem) }
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.tail.as_mut().map(|node| &mut node.elem) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_null()
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    // 把other的节点整个接到tail后面，O(1)，other变为空链表
+    pub fn append(&mut self, other: &mut Self) {
+        if other.head.is_null() {
+            return;
+        }
+        if self.tail.is_null() {
+            self.head = other.head;
+        } else {
+            unsafe {
+                (*self.tail).next = other.head;
+            }
+        }
+        self.tail = other.tail;
+        self.len += other.len;
+
+        other.head = ptr::null_mut();
+        other.tail = ptr::null_mut();
+        other.len = 0;
+    }
+
     pub fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
@@ -99,6 +158,41 @@ impl<T> List<T> {
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 依次push到队尾
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
 impl<T> Iterator for IntoIter<T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
@@ -132,9 +226,25 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
+// 序列化为从head（队头）到tail的序列
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        Ok(elems.into_iter().collect())
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
-        while let Some(_) =  self.pop() { }
+        self.clear();
     }
 }
 
@@ -179,6 +289,21 @@ mod test {
         assert_eq!(list.pop(), None);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let mut list = List::new();
+        list.push(1); list.push(2); list.push(3);
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+
+        let mut list: List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(list.pop(), Some(1));
+        list.push(4);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), &[2, 3, 4]);
+    }
+
     #[test]
     fn miri_food() {
         let mut list = List::new();
@@ -216,4 +341,74 @@ mod test {
 
         // Drop it on the ground and let the dtor exercise itself
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn double_ended() {
+        let mut list = List::new();
+        assert_eq!(list.peek_back(), None);
+        assert_eq!(list.peek_back_mut(), None);
+
+        list.push_front(2);
+        // 空链表push_front之后tail也要指向新节点
+        assert_eq!(list.peek_back(), Some(&2));
+        list.push(3);
+        list.push_front(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&3));
+
+        if let Some(x) = list.peek_back_mut() {
+            *x *= 10;
+        }
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [10, 2, 30]);
+
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.peek_back(), None);
+        list.push(4);
+        assert_eq!(list.peek(), Some(&4));
+        assert_eq!(list.peek_back(), Some(&4));
+    }
+
+    #[test]
+    fn append() {
+        let mut a: List<i32> = (1..=3).collect();
+        let mut b: List<i32> = (4..=5).collect();
+        a.append(&mut b);
+        assert_eq!(a.len(), 5);
+        assert!(b.is_empty());
+        assert_eq!(b.peek_back(), None);
+        assert_eq!(a.peek_back(), Some(&5));
+
+        // 接上之后继续push，tail要指向b原来的尾部
+        a.push(6);
+        b.push(7);
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), [7]);
+
+        let mut empty = List::new();
+        empty.append(&mut a);
+        assert_eq!(empty.len(), 6);
+        assert!(a.is_empty());
+        empty.append(&mut a);
+        assert_eq!(empty.pop(), Some(1));
+        assert_eq!(empty.len(), 5);
+    }
+
+    #[test]
+    fn traits() {
+        let mut list: List<String> = List::default();
+        list.extend(["a", "b"].iter().map(|s| s.to_string()));
+        let mut copy = list.clone();
+        copy.push_front("z".to_string());
+
+        assert_eq!(format!("{:?}", list), r#"["a", "b"]"#);
+        assert_eq!(format!("{:?}", copy), r#"["z", "a", "b"]"#);
+        assert_eq!(copy.len(), 3);
+        assert_eq!(copy.peek_back().map(String::as_str), Some("b"));
+    }
+}
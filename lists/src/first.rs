@@ -1,38 +1,67 @@
+use std::fmt::{self, Debug};
+use std::iter::FromIterator;
 use std::mem;
 
-// 对外公开List，隐藏Link，细节保留在内部
-pub struct List {
-    head: Link,
+// 对外公开List，隐藏Link和Node，细节保留在内部
+pub struct List<T> {
+    head: Link<T>,
 }
 
 // 编译器会消除`Empty`占用的额外空间，`More`因为包含了非空指针，
 // 所以不会被指针优化，也保证了尾部不会再分配多余的junk值
-enum Link {
+enum Link<T> {
     Empty,
-    More(Box<Node>),
+    More(Box<Node<T>>),
 }
 
-pub struct Node {
-    elem: i32,
-    next: Link,
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
 }
 
-impl List {
+pub struct IntoIter<T>(List<T>);
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+}
+
+impl<T> Link<T> {
+    // 和Option::as_deref一样，把&Link转成对节点的引用
+    fn as_node(&self) -> Option<&Node<T>> {
+        match self {
+            Link::Empty => None,
+            Link::More(node) => Some(node),
+        }
+    }
+
+    fn as_node_mut(&mut self) -> Option<&mut Node<T>> {
+        match self {
+            Link::Empty => None,
+            Link::More(node) => Some(node),
+        }
+    }
+}
+
+impl<T> List<T> {
     // 构建实例
     pub fn new() -> Self {
         List { head: Link::Empty }
     }
 
-    pub fn push(&mut self, elem: i32) {
+    pub fn push(&mut self, elem: T) {
         let new_node = Box::new(Node {
-            elem: elem,
+            elem,
             // mem::replace 允许从一个借用的透出一个值同时再放入
             next: mem::replace(&mut self.head, Link::Empty),
         });
         self.head = Link::More(new_node);
     }
 
-    pub fn pop(&mut self) -> Option<i32> {
+    pub fn pop(&mut self) -> Option<T> {
         // mem::replace 返回 dest: &mut self.head
         match mem::replace(&mut self.head, Link::Empty) {
             Link::Empty => None,
@@ -42,13 +71,169 @@ impl List {
             }
         }
     }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_node().map(|node| &node.elem)
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.as_node_mut().map(|node| &mut node.elem)
+    }
+
+    // 为了让List只占一个指针的大小，这里不单独记录长度，而是遍历一遍，O(n)
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self.head, Link::Empty)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_node(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_node_mut(),
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 逐个push，最后一个元素在栈顶
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// 从头到尾复制节点，保持原来的顺序
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        let mut list = Self::new();
+        // tail始终指向新链表最后一个Link，直接在尾部追加，不需要反转
+        let mut tail = &mut list.head;
+        for elem in self.iter() {
+            *tail = Link::More(Box::new(Node {
+                elem: elem.clone(),
+                next: Link::Empty,
+            }));
+            tail = match tail {
+                Link::More(node) => &mut node.next,
+                Link::Empty => unreachable!(),
+            };
+        }
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
 }
 
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_node();
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.take().map(|node| {
+            self.next = node.next.as_node_mut();
+            &mut node.elem
+        })
+    }
+}
+
+// 序列化为从栈顶开始的序列
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        // push插在头部，从最后一个元素开始push
+        Ok(elems.into_iter().rev().collect())
+    }
+}
 
 // 如果不实现drop 就会出现尾递归，有可能会导致栈溢出
 // list -> A -> B -> C list被drop后，就会尝试dropA 如此类推，这是一个递归代码
 // 如果node较多就有可能出现栈移除
-impl Drop for List {
+impl<T> Drop for List<T> {
     fn drop(&mut self) {
         let mut cur_link = mem::replace(&mut self.head, Link::Empty);
         while let Link::More(mut boxed_node) = cur_link {
@@ -61,7 +246,8 @@ impl Drop for List {
 
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{Link, List, Node};
+    use std::mem::size_of;
 
     #[test]
     fn basics() {
@@ -92,6 +278,88 @@ mod test {
         assert_eq!(list.pop(), None);
     }
 
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.peek(), Some(&2));
+
+        if let Some(value) = list.peek_mut() {
+            *value = 42;
+        }
+        assert_eq!(list.peek(), Some(&42));
+        assert_eq!(list.pop(), Some(42));
+        assert_eq!(list.peek(), Some(&1));
+    }
+
+    #[test]
+    fn iter() {
+        let mut list: List<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+
+        // 最后一个push的元素在最前面
+        let mut iter = list.iter();
+        assert_eq!(iter.next().map(String::as_str), Some("c"));
+        assert_eq!(iter.next().map(String::as_str), Some("b"));
+        assert_eq!(iter.next().map(String::as_str), Some("a"));
+        assert_eq!(iter.next(), None);
+
+        for elem in &mut list {
+            elem.push('!');
+        }
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), &["c!", "b!", "a!"]);
+    }
+
+    #[test]
+    fn traits() {
+        let mut list: List<i32> = List::default();
+        assert!(list.is_empty());
+        assert_eq!(list.len(), 0);
+        list.extend([1, 2, 3]);
+
+        let copy = list.clone();
+        assert_eq!(copy, list);
+        assert_eq!(format!("{:?}", copy), "[3, 2, 1]");
+
+        list.push(4);
+        assert_ne!(copy, list);
+        list.pop();
+        list.pop();
+        list.push(3);
+        assert_eq!(copy, list);
+    }
+
+    // Link<T>利用Box的非空指针优化，Empty用空指针表示，List只占一个指针的大小
+    #[test]
+    fn null_pointer_niche() {
+        assert_eq!(size_of::<List<i32>>(), size_of::<Box<i32>>());
+        assert_eq!(size_of::<List<[u64; 8]>>(), size_of::<usize>());
+        assert_eq!(size_of::<Link<String>>(), size_of::<Box<Node<String>>>());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[3,2,1]");
+
+        let mut list: List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), None);
+    }
+
     #[test]
     fn long_list() {
         let mut list = List::new();
@@ -101,6 +369,3 @@ mod test {
         drop(list);
     }
 }
-
-
-
@@ -1,25 +1,38 @@
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::cell::{RefCell, Ref, RefMut};
 
 pub struct List<T> {
     head: Link<T>,
     tail: Link<T>,
+    len: usize,
 }
 
 pub struct IntoIter<T>(List<T>);
 
 type Link<T> = Option<Rc<RefCell<Node<T>>>>;
 
+// 指向前一个节点的是弱引用，相邻的节点之间不会形成Rc的循环引用，
+// 节点只被前一个节点的next（或者head）拥有，tail节点另外被list.tail拥有
+type WeakLink<T> = Option<Weak<RefCell<Node<T>>>>;
+
 struct Node<T> {
     elem: T,
     next: Link<T>,
-    prev: Link<T>,
+    prev: WeakLink<T>,
+}
+
+// stats()的结果，按从head到tail的顺序记录每个节点的引用计数
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub len: usize,
+    pub strong_counts: Vec<usize>,
+    pub weak_counts: Vec<usize>,
 }
 
 impl<T> Node<T> {
     fn new(elem: T) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Node {
-            elem: elem,
+            elem,
             next: None,
             prev: None,
         }))
@@ -31,16 +44,25 @@ impl<T> List<T> {
         List {
             head: None,
             tail: None,
+            len: 0,
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn push_front(&mut self, elem: T) {
         let new_head = Node::new(elem);
         match self.head.take() {
             // 如果链表中存在node，更改旧链表的head指向新node，同时新node的prev也需要更改
             // 用到RefCell的borrow_mut 可变借用
             Some(old_head) => {
-                old_head.borrow_mut().prev = Some(new_head.clone());
+                old_head.borrow_mut().prev = Some(Rc::downgrade(&new_head));
                 new_head.borrow_mut().next = Some(old_head);
                 self.head = Some(new_head);
             }
@@ -50,6 +72,7 @@ impl<T> List<T> {
                 self.head = Some(new_head);
             }
         }
+        self.len += 1;
     }
 
     pub fn push_back(&mut self, elem: T) {
@@ -57,7 +80,7 @@ impl<T> List<T> {
         match self.tail.take() {
             Some(old_tail) => {
                 old_tail.borrow_mut().next = Some(new_tail.clone());
-                new_tail.borrow_mut().prev = Some(old_tail);
+                new_tail.borrow_mut().prev = Some(Rc::downgrade(&old_tail));
                 self.tail = Some(new_tail);
             }
             None => {
@@ -65,6 +88,7 @@ impl<T> List<T> {
                 self.tail = Some(new_tail);
             }
         }
+        self.len += 1;
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
@@ -80,6 +104,7 @@ impl<T> List<T> {
                     self.tail.take();
                 }
             }
+            self.len -= 1;
             // Refcell.into_inner()直接取走所有权
             // 因为外面还包裹着RC，所以需要用try_unwrap()获取
             Rc::try_unwrap(old_head).ok().unwrap().into_inner().elem
@@ -88,7 +113,8 @@ impl<T> List<T> {
 
     pub fn pop_back(&mut self) -> Option<T> {
         self.tail.take().map(|old_tail|{
-            match old_tail.borrow_mut().prev.take() {
+            // 前一个节点被它自己的前驱（或者head）拥有，一定还活着
+            match old_tail.borrow_mut().prev.take().and_then(|prev| prev.upgrade()) {
                 Some(new_tail) => {
                     new_tail.borrow_mut().next.take();
                     self.tail = Some(new_tail);
@@ -97,6 +123,7 @@ impl<T> List<T> {
                     self.head.take();
                 }
             }
+            self.len -= 1;
             Rc::try_unwrap(old_tail).ok().unwrap().into_inner().elem
         })
     }
@@ -130,7 +157,95 @@ impl<T> List<T> {
     pub fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
-    
+
+    // 调试用：检查链表的结构并返回每个节点的引用计数，结构不对时panic。
+    // 检查的不变量：节点个数等于len；相邻节点的next和prev互相指向对方；head没有prev，最后一个节点就是tail；
+    // 每个节点只有一个强引用（tail节点另外被list.tail引用），弱引用只来自后一个节点的prev
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            len: 0,
+            strong_counts: Vec::new(),
+            weak_counts: Vec::new(),
+        };
+        let mut prev: Link<T> = None;
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            {
+                let borrowed = node.borrow();
+                let back = borrowed.prev.as_ref().and_then(Weak::upgrade);
+                match (&back, &prev) {
+                    (Some(back), Some(prev)) => {
+                        assert!(Rc::ptr_eq(back, prev), "prev does not point to the previous node")
+                    }
+                    (None, None) => {}
+                    _ => panic!("prev link is inconsistent at index {}", stats.len),
+                }
+                let is_tail = borrowed.next.is_none();
+                if is_tail {
+                    let tail = self.tail.as_ref().expect("non-empty list without tail");
+                    assert!(Rc::ptr_eq(tail, &node), "last node is not the tail");
+                }
+
+                // 减去遍历时临时持有的克隆
+                let strong = Rc::strong_count(&node) - 1;
+                let weak = Rc::weak_count(&node);
+                assert_eq!(strong, 1 + usize::from(is_tail), "unexpected strong count at index {}", stats.len);
+                assert_eq!(weak, usize::from(!is_tail), "unexpected weak count at index {}", stats.len);
+                stats.strong_counts.push(strong);
+                stats.weak_counts.push(weak);
+                stats.len += 1;
+                cur = borrowed.next.clone();
+            }
+            prev = Some(node);
+        }
+        if stats.len == 0 {
+            assert!(self.tail.is_none(), "empty list with a tail");
+        }
+        assert_eq!(stats.len, self.len, "len does not match the number of nodes");
+        stats
+    }
+
+    // 借用的迭代器需要返回Ref<T>，但Ref的生命周期只能和当前节点的borrow()一样长，
+    // 没办法在Iterator::next里返回，所以用访问者的方式：依次借用每个节点，把元素的引用交给f。
+    // f执行期间当前节点处于借用状态，所以f里不能再peek这个节点
+
+    // 从head到tail依次访问
+    pub fn for_each<F: FnMut(&T)>(&self, mut f: F) {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            f(&node.elem);
+            cur = node.next.clone();
+        }
+    }
+
+    pub fn for_each_mut<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let mut node = node.borrow_mut();
+            f(&mut node.elem);
+            cur = node.next.clone();
+        }
+    }
+
+    // 从tail到head反向访问
+    pub fn for_each_rev<F: FnMut(&T)>(&self, mut f: F) {
+        let mut cur = self.tail.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            f(&node.elem);
+            cur = node.prev.as_ref().and_then(Weak::upgrade);
+        }
+    }
+
+    pub fn for_each_rev_mut<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        let mut cur = self.tail.clone();
+        while let Some(node) = cur {
+            let mut node = node.borrow_mut();
+            f(&mut node.elem);
+            cur = node.prev.as_ref().and_then(Weak::upgrade);
+        }
+    }
 }
 
 impl<T> Iterator for IntoIter<T> {
@@ -146,6 +261,35 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
+// 序列化为从head到tail的序列
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            seq.serialize_element(&node.elem)?;
+            cur = node.next.clone();
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        let mut list = List::new();
+        for elem in elems {
+            list.push_back(elem);
+        }
+        Ok(list)
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         while self.pop_front().is_some() {}
@@ -154,7 +298,9 @@ impl<T> Drop for List<T> {
 
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{List, Stats};
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
 
     #[test]
     fn basics() {
@@ -227,6 +373,21 @@ mod test {
         assert_eq!(&mut *list.peek_back_mut().unwrap(), &mut 1);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let mut list = List::new();
+        list.push_front(2); list.push_back(3); list.push_front(1);
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[1,2,3]");
+
+        let list: List<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(&*list.peek_front().unwrap(), &1);
+        assert_eq!(&*list.peek_back().unwrap(), &3);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), &[1, 2, 3]);
+    }
+
     #[test]
     fn into_iter() {
         let mut list = List::new();
@@ -239,4 +400,138 @@ mod test {
         assert_eq!(iter.next_back(), None);
         assert_eq!(iter.next(), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+
+        list.push_front(1);
+        list.push_back(2);
+        list.push_front(0);
+        assert_eq!(list.len(), 3);
+
+        list.pop_back();
+        assert_eq!(list.len(), 2);
+        list.pop_front();
+        list.pop_front();
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn for_each() {
+        let mut list = List::new();
+        list.push_back(1); list.push_back(2); list.push_back(3);
+
+        let mut forward = Vec::new();
+        list.for_each(|elem| forward.push(*elem));
+        assert_eq!(forward, [1, 2, 3]);
+
+        let mut backward = Vec::new();
+        list.for_each_rev(|elem| backward.push(*elem));
+        assert_eq!(backward, [3, 2, 1]);
+
+        list.for_each_mut(|elem| *elem *= 10);
+        let mut i = 0;
+        list.for_each_rev_mut(|elem| {
+            i += 1;
+            *elem += i;
+        });
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [13, 22, 31]);
+
+        // 空链表不会调用f
+        let mut empty: List<i32> = List::new();
+        empty.for_each(|_| unreachable!());
+        empty.for_each_rev_mut(|_| unreachable!());
+    }
+
+    #[test]
+    fn stats() {
+        let mut list = List::new();
+        assert_eq!(list.stats(), Stats { len: 0, strong_counts: vec![], weak_counts: vec![] });
+
+        list.push_back(1);
+        // 唯一的节点同时是head和tail
+        assert_eq!(list.stats(), Stats { len: 1, strong_counts: vec![2], weak_counts: vec![0] });
+
+        list.push_back(2); list.push_front(0); list.push_back(3);
+        assert_eq!(
+            list.stats(),
+            Stats { len: 4, strong_counts: vec![1, 1, 1, 2], weak_counts: vec![1, 1, 1, 0] }
+        );
+
+        list.pop_back(); list.pop_front();
+        assert_eq!(list.stats(), Stats { len: 2, strong_counts: vec![1, 2], weak_counts: vec![1, 0] });
+    }
+
+    // 记录drop的次数
+    struct DropCounter<'a> {
+        drops: &'a Cell<usize>,
+        panic_on_drop: bool,
+    }
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panic_on_drop {
+                panic!("drop panicked");
+            }
+        }
+    }
+
+    fn counter(drops: &Cell<usize>) -> DropCounter<'_> {
+        DropCounter { drops, panic_on_drop: false }
+    }
+
+    #[test]
+    fn no_leaks() {
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        for _ in 0..10 {
+            list.push_back(counter(&drops));
+            list.push_front(counter(&drops));
+        }
+        drop(list.pop_front());
+        drop(list.pop_back());
+        assert_eq!(drops.get(), 2);
+
+        drop(list);
+        assert_eq!(drops.get(), 20);
+    }
+
+    // 元素的drop panic后，Drop的循环被打断，剩下的节点靠Rc自己的析构释放，
+    // 如果prev是强引用，这些节点互相引用就会泄漏
+    #[test]
+    fn no_leaks_on_panic() {
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        for i in 0..10 {
+            list.push_back(DropCounter { drops: &drops, panic_on_drop: i == 3 });
+        }
+        let result = panic::catch_unwind(AssertUnwindSafe(|| drop(list)));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 10);
+    }
+
+    // 节点之间没有循环引用，不经过Drop的循环，只靠Rc的引用计数也能释放全部节点
+    #[test]
+    fn no_cycles() {
+        let drops = Cell::new(0);
+        let mut list = List::new();
+        for _ in 0..5 {
+            list.push_back(counter(&drops));
+        }
+        let head = list.head.take();
+        let tail = list.tail.take();
+        list.len = 0;
+        drop(list);
+        assert_eq!(drops.get(), 0);
+
+        drop(tail);
+        drop(head);
+        assert_eq!(drops.get(), 5);
+    }
+}
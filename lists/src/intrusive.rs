@@ -0,0 +1,693 @@
+use std::cell::Cell;
+use std::fmt::{self, Debug};
+use std::marker::{PhantomData, PhantomPinned};
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::linkedlist::{unlink, LinkOps};
+
+// 侵入式链表：前后指针不在链表自己分配的节点里，而是作为Links字段嵌在用户的结构体中，
+// 同一个对象可以在不同的链表之间移动而不需要任何分配。
+// 链表只借用对象，对象的生命周期'a必须比链表长，并且对象被Pin住，在链表中时不会被移动。
+
+// Links中的指针指向整个Item而不是Links字段本身，擦除成NonNull<()>，
+// 这样从指针还原出&Item时不需要根据字段偏移做指针运算
+type RawLink = Option<NonNull<()>>;
+
+type Link<A> = Option<NonNull<<A as Adapter>::Item>>;
+
+// 每个链表有唯一的id，Links记录自己属于哪个链表，防止从别的链表中删除
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// 嵌入在用户结构体中的链接字段
+pub struct Links {
+    front: Cell<RawLink>,
+    back: Cell<RawLink>,
+    // 所在链表的id，0表示不在任何链表中
+    owner: Cell<usize>,
+    // 链表中保存着指向对象的指针，对象不能是Unpin的
+    _pin: PhantomPinned,
+}
+
+/// 把用户的结构体和其中的Links字段对应起来
+///
+/// # Safety
+///
+/// 对同一个对象，`links`每次都必须返回该对象自己的同一个`Links`字段。
+pub unsafe trait Adapter {
+    type Item;
+
+    fn links(item: &Self::Item) -> &Links;
+}
+
+pub struct IntrusiveList<'a, A: Adapter> {
+    front: Link<A>,
+    back: Link<A>,
+    len: usize,
+    id: usize,
+    _boo: PhantomData<(&'a A::Item, A)>,
+}
+
+pub struct Iter<'l, 'a, A: Adapter> {
+    front: Link<A>,
+    back: Link<A>,
+    len: usize,
+    _boo: PhantomData<&'l IntrusiveList<'a, A>>,
+}
+
+pub struct Cursor<'l, 'a, A: Adapter> {
+    cur: Link<A>,
+    list: &'l IntrusiveList<'a, A>,
+}
+
+pub struct CursorMut<'l, 'a, A: Adapter> {
+    cur: Link<A>,
+    list: &'l mut IntrusiveList<'a, A>,
+}
+
+// 通过Adapter找到Item中的Links，读写前后指针，供linkedlist::unlink使用
+struct AdapterOps<A>(PhantomData<A>);
+
+impl<A> AdapterOps<A> {
+    fn new() -> Self {
+        AdapterOps(PhantomData)
+    }
+}
+
+impl<A: Adapter> LinkOps for AdapterOps<A> {
+    type Node = A::Item;
+
+    unsafe fn front(&self, node: NonNull<A::Item>) -> Link<A> {
+        links::<A>(node).front.get().map(NonNull::cast)
+    }
+
+    unsafe fn back(&self, node: NonNull<A::Item>) -> Link<A> {
+        links::<A>(node).back.get().map(NonNull::cast)
+    }
+
+    unsafe fn set_front(&self, node: NonNull<A::Item>, link: Link<A>) {
+        links::<A>(node).front.set(link.map(NonNull::cast));
+    }
+
+    unsafe fn set_back(&self, node: NonNull<A::Item>, link: Link<A>) {
+        links::<A>(node).back.set(link.map(NonNull::cast));
+    }
+}
+
+// 链表中的对象都被借用了'a，在链表存活期间一直有效
+unsafe fn links<'n, A: Adapter>(node: NonNull<A::Item>) -> &'n Links
+where
+    A::Item: 'n,
+{
+    A::links(&*node.as_ptr())
+}
+
+impl Links {
+    pub const fn new() -> Self {
+        Links {
+            front: Cell::new(None),
+            back: Cell::new(None),
+            owner: Cell::new(0),
+            _pin: PhantomPinned,
+        }
+    }
+
+    // 当前是否在某个链表中
+    pub fn is_linked(&self) -> bool {
+        self.owner.get() != 0
+    }
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Links {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Links").field("linked", &self.is_linked()).finish()
+    }
+}
+
+impl<'a, A: Adapter> IntrusiveList<'a, A> {
+    pub fn new() -> Self {
+        IntrusiveList {
+            front: None,
+            back: None,
+            len: 0,
+            id: NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed),
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<Pin<&'a A::Item>> {
+        unsafe { self.front.map(|node| pin(node)) }
+    }
+
+    pub fn back(&self) -> Option<Pin<&'a A::Item>> {
+        unsafe { self.back.map(|node| pin(node)) }
+    }
+
+    // 对象是否在这个链表中
+    pub fn contains(&self, item: &A::Item) -> bool {
+        A::links(item).owner.get() == self.id
+    }
+
+    // 把对象接入链表，记录所属的链表，返回对象的指针
+    // 一个Links同一时间只能在一个链表中，否则前后指针会被覆盖
+    fn link(&mut self, item: Pin<&'a A::Item>) -> NonNull<A::Item> {
+        let item = item.get_ref();
+        let links = A::links(item);
+        assert!(!links.is_linked(), "item is already linked into a list");
+        links.owner.set(self.id);
+        self.len += 1;
+        NonNull::from(item)
+    }
+
+    pub fn push_front(&mut self, item: Pin<&'a A::Item>) {
+        let ops = AdapterOps::<A>::new();
+        let new = self.link(item);
+        unsafe {
+            if let Some(old) = self.front {
+                ops.set_front(old, Some(new));
+                ops.set_back(new, Some(old));
+            } else {
+                self.back = Some(new);
+            }
+        }
+        self.front = Some(new);
+    }
+
+    // push_front的镜像操作
+    pub fn push_back(&mut self, item: Pin<&'a A::Item>) {
+        let ops = AdapterOps::<A>::new();
+        let new = self.link(item);
+        unsafe {
+            if let Some(old) = self.back {
+                ops.set_back(old, Some(new));
+                ops.set_front(new, Some(old));
+            } else {
+                self.front = Some(new);
+            }
+        }
+        self.back = Some(new);
+    }
+
+    pub fn pop_front(&mut self) -> Option<Pin<&'a A::Item>> {
+        let node = self.front?;
+        unsafe { Some(self.unlink(node)) }
+    }
+
+    pub fn pop_back(&mut self) -> Option<Pin<&'a A::Item>> {
+        let node = self.back?;
+        unsafe { Some(self.unlink(node)) }
+    }
+
+    // 把对象从链表中删除，对象不在这个链表中时返回false
+    pub fn remove(&mut self, item: &A::Item) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        unsafe {
+            self.unlink(NonNull::from(item));
+        }
+        true
+    }
+
+    // 删除所有对象，对象之后可以再放进其他链表
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // node必须在这个链表中
+    unsafe fn unlink(&mut self, node: NonNull<A::Item>) -> Pin<&'a A::Item> {
+        unlink(&AdapterOps::<A>::new(), node, &mut self.front, &mut self.back);
+        links::<A>(node).owner.set(0);
+        self.len -= 1;
+        pin(node)
+    }
+
+    pub fn iter(&self) -> Iter<'_, 'a, A> {
+        Iter {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    // 只读cursor，指向链表的第一个对象
+    pub fn cursor_front(&self) -> Cursor<'_, 'a, A> {
+        Cursor {
+            cur: self.front,
+            list: self,
+        }
+    }
+
+    // 只读cursor，指向链表的最后一个对象
+    pub fn cursor_back(&self) -> Cursor<'_, 'a, A> {
+        Cursor {
+            cur: self.back,
+            list: self,
+        }
+    }
+
+    // 和LinkedList::cursor_mut一样，从幽灵节点开始
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, 'a, A> {
+        CursorMut {
+            cur: None,
+            list: self,
+        }
+    }
+}
+
+// 对象在链表中时一直被Pin着，并且借用了'a
+unsafe fn pin<'a, T>(node: NonNull<T>) -> Pin<&'a T> {
+    Pin::new_unchecked(&*node.as_ptr())
+}
+
+impl<'a, A: Adapter> Default for IntrusiveList<'a, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, A: Adapter> Debug for IntrusiveList<'a, A>
+where
+    A::Item: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// 链表被drop时把所有对象的Links重置，之后它们可以放进其他链表
+impl<'a, A: Adapter> Drop for IntrusiveList<'a, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'l, 'a, A: Adapter> IntoIterator for &'l IntrusiveList<'a, A> {
+    type IntoIter = Iter<'l, 'a, A>;
+    type Item = Pin<&'a A::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, 'a, A: Adapter> Iterator for Iter<'l, 'a, A> {
+    type Item = Pin<&'a A::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = AdapterOps::<A>::new().back(node);
+                pin(node)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+// 从后向前遍历
+impl<'l, 'a, A: Adapter> DoubleEndedIterator for Iter<'l, 'a, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = AdapterOps::<A>::new().front(node);
+                pin(node)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'l, 'a, A: Adapter> ExactSizeIterator for Iter<'l, 'a, A> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'l, 'a, A: Adapter> Cursor<'l, 'a, A> {
+    // cur 向后移动操作，走到幽灵节点后再从头开始
+    pub fn move_next(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { AdapterOps::<A>::new().back(cur) },
+            None => self.list.front,
+        };
+    }
+
+    // move_next的镜像操作
+    pub fn move_prev(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { AdapterOps::<A>::new().front(cur) },
+            None => self.list.back,
+        };
+    }
+
+    pub fn current(&self) -> Option<Pin<&'a A::Item>> {
+        unsafe { self.cur.map(|node| pin(node)) }
+    }
+
+    pub fn peek_next(&self) -> Option<Pin<&'a A::Item>> {
+        let next = match self.cur {
+            Some(cur) => unsafe { AdapterOps::<A>::new().back(cur) },
+            None => self.list.front,
+        };
+        unsafe { next.map(|node| pin(node)) }
+    }
+
+    pub fn peek_prev(&self) -> Option<Pin<&'a A::Item>> {
+        let prev = match self.cur {
+            Some(cur) => unsafe { AdapterOps::<A>::new().front(cur) },
+            None => self.list.back,
+        };
+        unsafe { prev.map(|node| pin(node)) }
+    }
+}
+
+impl<'l, 'a, A: Adapter> CursorMut<'l, 'a, A> {
+    pub fn move_next(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { AdapterOps::<A>::new().back(cur) },
+            None => self.list.front,
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { AdapterOps::<A>::new().front(cur) },
+            None => self.list.back,
+        };
+    }
+
+    // 链表只持有共享引用，所以这里返回的也是共享引用，修改需要通过对象内部的Cell等
+    pub fn current(&self) -> Option<Pin<&'a A::Item>> {
+        unsafe { self.cur.map(|node| pin(node)) }
+    }
+
+    pub fn as_cursor(&self) -> Cursor<'_, 'a, A> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+        }
+    }
+
+    // 删除当前对象并返回，cursor移动到下一个对象
+    pub fn remove_current(&mut self) -> Option<Pin<&'a A::Item>> {
+        let cur = self.cur?;
+        unsafe {
+            self.cur = AdapterOps::<A>::new().back(cur);
+            Some(self.list.unlink(cur))
+        }
+    }
+
+    // 在cursor前插入对象，cursor指向幽灵节点时插入到链表尾部
+    pub fn insert_before(&mut self, item: Pin<&'a A::Item>) {
+        let Some(cur) = self.cur else {
+            self.list.push_back(item);
+            return;
+        };
+        let ops = AdapterOps::<A>::new();
+        unsafe {
+            match ops.front(cur) {
+                Some(prev) => {
+                    let new = self.list.link(item);
+                    ops.set_back(prev, Some(new));
+                    ops.set_front(new, Some(prev));
+                    ops.set_back(new, Some(cur));
+                    ops.set_front(cur, Some(new));
+                }
+                None => self.list.push_front(item),
+            }
+        }
+    }
+
+    // 在cursor后插入对象，cursor指向幽灵节点时插入到链表头部
+    pub fn insert_after(&mut self, item: Pin<&'a A::Item>) {
+        let Some(cur) = self.cur else {
+            self.list.push_front(item);
+            return;
+        };
+        let ops = AdapterOps::<A>::new();
+        unsafe {
+            match ops.back(cur) {
+                Some(next) => {
+                    let new = self.list.link(item);
+                    ops.set_front(next, Some(new));
+                    ops.set_back(new, Some(next));
+                    ops.set_front(new, Some(cur));
+                    ops.set_back(cur, Some(new));
+                }
+                None => self.list.push_back(item),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Adapter, IntrusiveList, Links};
+    use std::cell::Cell;
+    use std::pin::{pin, Pin};
+
+    // 同一个任务可以同时在两个队列中，分别使用不同的Links字段
+    #[derive(Debug)]
+    struct Task {
+        id: u32,
+        runs: Cell<u32>,
+        queue: Links,
+        all: Links,
+    }
+
+    impl Task {
+        fn new(id: u32) -> Self {
+            Task {
+                id,
+                runs: Cell::new(0),
+                queue: Links::new(),
+                all: Links::new(),
+            }
+        }
+    }
+
+    struct QueueAdapter;
+
+    unsafe impl Adapter for QueueAdapter {
+        type Item = Task;
+
+        fn links(item: &Task) -> &Links {
+            &item.queue
+        }
+    }
+
+    struct AllAdapter;
+
+    unsafe impl Adapter for AllAdapter {
+        type Item = Task;
+
+        fn links(item: &Task) -> &Links {
+            &item.all
+        }
+    }
+
+    fn ids<A: Adapter<Item = Task>>(list: &IntrusiveList<'_, A>) -> Vec<u32> {
+        let from_front: Vec<_> = list.iter().map(|t| t.id).collect();
+        let mut from_back: Vec<_> = list.iter().rev().map(|t| t.id).collect();
+        from_back.reverse();
+        assert_eq!(from_front, from_back);
+        assert_eq!(from_front.len(), list.len());
+        from_front
+    }
+
+    #[test]
+    fn basics() {
+        let tasks: Vec<Pin<Box<Task>>> = (0..4).map(|i| Box::pin(Task::new(i))).collect();
+        let mut list: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        assert!(list.is_empty());
+        assert!(list.pop_front().is_none());
+
+        list.push_back(tasks[1].as_ref());
+        list.push_back(tasks[2].as_ref());
+        list.push_front(tasks[0].as_ref());
+        assert_eq!(ids(&list), &[0, 1, 2]);
+        assert!(list.contains(&tasks[1]));
+        assert!(!list.contains(&tasks[3]));
+        assert!(tasks[0].queue.is_linked());
+
+        // 删除中间、不在链表中的、头部的对象
+        assert!(list.remove(&tasks[1]));
+        assert!(!list.remove(&tasks[1]));
+        assert!(!list.remove(&tasks[3]));
+        assert!(!tasks[1].queue.is_linked());
+        assert_eq!(ids(&list), &[0, 2]);
+        assert!(list.remove(&tasks[0]));
+        assert_eq!(list.front().unwrap().id, 2);
+        assert_eq!(list.back().unwrap().id, 2);
+
+        list.push_front(tasks[1].as_ref());
+        assert_eq!(list.pop_back().unwrap().id, 2);
+        assert_eq!(list.pop_back().unwrap().id, 1);
+        assert!(list.pop_back().is_none());
+        assert!(list.is_empty());
+        assert!(tasks.iter().all(|t| !t.queue.is_linked()));
+    }
+
+    #[test]
+    fn move_between_lists() {
+        let a = pin!(Task::new(1));
+        let b = pin!(Task::new(2));
+        let (a, b) = (a.into_ref(), b.into_ref());
+
+        let mut ready: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        let mut waiting: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        let mut all: IntrusiveList<AllAdapter> = IntrusiveList::new();
+
+        all.push_back(a);
+        all.push_back(b);
+        ready.push_back(a);
+        ready.push_back(b);
+
+        // 从ready移到waiting，不需要任何分配
+        let task = ready.pop_front().unwrap();
+        task.runs.set(task.runs.get() + 1);
+        waiting.push_back(task);
+        assert_eq!(ids(&ready), &[2]);
+        assert_eq!(ids(&waiting), &[1]);
+        assert_eq!(ids(&all), &[1, 2]);
+
+        // 不能从别的链表中删除
+        assert!(!ready.remove(&a));
+        assert!(waiting.remove(&a));
+        ready.push_front(a);
+        assert_eq!(ids(&ready), &[1, 2]);
+        assert_eq!(a.runs.get(), 1);
+        assert_eq!(format!("{:?}", waiting), "[]");
+
+        // 链表被drop后对象的Links被重置
+        drop(ready);
+        assert!(!a.queue.is_linked());
+        assert!(a.all.is_linked());
+        waiting.push_back(a);
+        assert_eq!(ids(&waiting), &[1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_twice() {
+        let task = Box::pin(Task::new(0));
+        let mut first: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        let mut second: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        first.push_back(task.as_ref());
+        second.push_back(task.as_ref());
+    }
+
+    #[test]
+    fn cursor() {
+        let tasks: Vec<Pin<Box<Task>>> = (0..6).map(|i| Box::pin(Task::new(i))).collect();
+        let mut list: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        for task in &tasks[1..4] {
+            list.push_back(task.as_ref());
+        }
+
+        let mut cursor = list.cursor_front();
+        assert_eq!(cursor.current().unwrap().id, 1);
+        assert!(cursor.peek_prev().is_none());
+        cursor.move_prev();
+        assert!(cursor.current().is_none());
+        assert_eq!(cursor.peek_next().unwrap().id, 1);
+        assert_eq!(cursor.peek_prev().unwrap().id, 3);
+        let back = list.cursor_back();
+        assert_eq!(back.current().unwrap().id, 3);
+
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(tasks[0].as_ref());
+        cursor.insert_before(tasks[5].as_ref());
+        cursor.move_prev();
+        assert_eq!(cursor.current().unwrap().id, 5);
+        cursor.insert_before(tasks[4].as_ref());
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.as_cursor().current().unwrap().id, 1);
+        assert_eq!(cursor.remove_current().unwrap().id, 1);
+        assert_eq!(cursor.current().unwrap().id, 2);
+        cursor.insert_before(tasks[1].as_ref());
+        assert_eq!(ids(&list), &[0, 1, 2, 3, 4, 5]);
+
+        // 删除所有奇数任务
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        while let Some(task) = cursor.current() {
+            if task.id % 2 == 1 {
+                cursor.remove_current();
+            } else {
+                cursor.move_next();
+            }
+        }
+        assert_eq!(ids(&list), &[0, 2, 4]);
+        assert!(!tasks[5].queue.is_linked());
+    }
+
+    // cargo +nightly miri test intrusive
+    #[test]
+    fn miri_food() {
+        let tasks: Vec<Pin<Box<Task>>> = (0..8).map(|i| Box::pin(Task::new(i))).collect();
+        let mut even: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        let mut odd: IntrusiveList<QueueAdapter> = IntrusiveList::new();
+        let mut all: IntrusiveList<AllAdapter> = IntrusiveList::new();
+
+        for task in &tasks {
+            all.push_front(task.as_ref());
+            even.push_back(task.as_ref());
+        }
+
+        let mut cursor = even.cursor_mut();
+        cursor.move_next();
+        while let Some(task) = cursor.current() {
+            if task.id % 2 == 1 {
+                cursor.remove_current();
+                odd.push_back(task);
+            } else {
+                cursor.move_next();
+            }
+        }
+        assert!(even.remove(&tasks[6]));
+        even.push_back(tasks[6].as_ref());
+        for task in all.iter() {
+            task.runs.set(task.runs.get() + task.id);
+        }
+
+        assert_eq!(ids(&even), &[0, 2, 4, 6]);
+        assert_eq!(ids(&odd), &[1, 3, 5, 7]);
+        assert_eq!(ids(&all), &[7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(odd.iter().map(|t| t.runs.get()).sum::<u32>(), 16);
+
+        while let Some(task) = all.pop_back() {
+            assert!(!task.all.is_linked());
+        }
+        drop(even);
+        odd.clear();
+        assert!(tasks.iter().all(|t| !t.queue.is_linked() && !t.all.is_linked()));
+    }
+}
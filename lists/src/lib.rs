@@ -4,4 +4,7 @@ pub mod third;
 pub mod fourth;
 pub mod fifth;
 pub mod linkedlist;
-
+pub mod intrusive;
+pub mod concurrent;
+pub mod unrolled;
+pub mod skiplist;
@@ -1,14 +1,18 @@
+use std::alloc::{self, Layout};
 use std::cmp::Ordering;
 use std::fmt::{self, Debug};
 use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
-use std::ptr::NonNull;
+use std::mem::{self, align_of, size_of};
+use std::ptr::{self, NonNull};
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 
-pub struct LinkedList<T> {
+pub struct LinkedList<T, A: NodeAlloc = Global> {
     front: Link<T>,
     back: Link<T>,
     len: usize,
+    alloc: A,
     /// 让编译器不再给出 T 未被使用的警告或者错误。
     _boo: PhantomData<T>,
 }
@@ -35,26 +39,257 @@ pub struct IterMut<'a, T> {
     _boo: PhantomData<&'a mut T>,
 }
 
-pub struct IntoIter<T> {
-    list: LinkedList<T>,
+pub struct IntoIter<T, A: NodeAlloc = Global> {
+    list: LinkedList<T, A>,
 }
 
-pub struct CursorMut<'a, T> {
+pub struct Cursor<'a, T, A: NodeAlloc = Global> {
     cur: Link<T>,
-    list: &'a mut LinkedList<T>,
+    list: &'a LinkedList<T, A>,
     index: Option<usize>,
 }
 
+pub struct CursorMut<'a, T, A: NodeAlloc = Global> {
+    cur: Link<T>,
+    list: &'a mut LinkedList<T, A>,
+    index: Option<usize>,
+}
+
+// 指向链表中某个节点的句柄，节点的地址在它被删除之前不会改变
+// 配合外部的索引（比如哈希表）可以O(1)地访问、移动、删除任意节点，但链表本身不检查句柄是否有效
+pub struct Handle<T>(NonNull<Node<T>>);
+
+pub struct ExtractIf<'a, T, F, A: NodeAlloc = Global>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    cursor: CursorMut<'a, T, A>,
+    pred: F,
+}
+
+/// 节点分配器
+///
+/// 链表的每个节点都通过它分配和释放，默认的`Global`直接使用全局分配器，
+/// `NodePool`会把释放的节点缓存起来留给下一次分配。
+///
+/// # Safety
+///
+/// `allocate`返回的内存必须满足传入的`layout`，并且在`deallocate`之前一直有效。
+/// 节点会通过`append`、`splice_*`等操作在链表之间移动，所以同一类型的任意实例
+/// 都必须能够释放其他实例分配的内存。切割链表时新链表使用`Default`创建的分配器。
+pub unsafe trait NodeAlloc: Default {
+    fn allocate(&mut self, layout: Layout) -> NonNull<u8>;
+
+    /// # Safety
+    ///
+    /// `ptr`必须是同一类型的分配器用相同的`layout`分配的，并且还没有被释放。
+    unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout);
+
+    // 预留至少additional个空闲节点，之后的分配不再需要向系统申请内存
+    fn reserve(&mut self, _layout: Layout, _additional: usize) {}
+
+    // 把缓存的空闲节点还给系统
+    fn shrink_to_fit(&mut self) {}
+
+    // 当前缓存的空闲节点个数
+    fn spare(&self) -> usize {
+        0
+    }
+}
+
+// 直接使用全局分配器，和之前Box::new的行为相同
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl NodeAlloc for Global {
+    fn allocate(&mut self, layout: Layout) -> NonNull<u8> {
+        unsafe {
+            NonNull::new(alloc::alloc(layout)).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        }
+    }
+
+    unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout)
+    }
+}
+
+// 空闲节点链表，next直接写在被释放节点的内存里，不需要额外的分配
+#[derive(Debug, Default)]
+pub struct NodePool {
+    free: Option<NonNull<FreeBlock>>,
+    // 第一次分配时记录节点的layout，只缓存这一种大小的内存
+    layout: Option<Layout>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct FreeBlock {
+    next: Option<NonNull<FreeBlock>>,
+}
+
+impl NodePool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 小于FreeBlock的内存无法串进空闲链表，其他layout的内存也不缓存，这些都直接交给Global
+    fn caches(&mut self, layout: Layout) -> bool {
+        if layout.size() < size_of::<FreeBlock>() || layout.align() < align_of::<FreeBlock>() {
+            return false;
+        }
+        *self.layout.get_or_insert(layout) == layout
+    }
+}
+
+unsafe impl NodeAlloc for NodePool {
+    fn allocate(&mut self, layout: Layout) -> NonNull<u8> {
+        if self.caches(layout) {
+            if let Some(block) = self.free {
+                // 从空闲链表头部取出一个节点
+                self.free = unsafe { (*block.as_ptr()).next };
+                self.len -= 1;
+                return block.cast();
+            }
+        }
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        if self.caches(layout) {
+            // 把节点放回空闲链表头部
+            let block = ptr.cast::<FreeBlock>();
+            block.as_ptr().write(FreeBlock { next: self.free });
+            self.free = Some(block);
+            self.len += 1;
+        } else {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    fn reserve(&mut self, layout: Layout, additional: usize) {
+        if !self.caches(layout) {
+            return;
+        }
+        while self.len < additional {
+            let ptr = Global.allocate(layout);
+            unsafe { self.deallocate(ptr, layout) };
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        while let Some(block) = self.free {
+            unsafe {
+                self.free = (*block.as_ptr()).next;
+                // 有空闲节点说明layout已经记录过了
+                Global.deallocate(block.cast(), self.layout.unwrap());
+            }
+        }
+        self.len = 0;
+    }
+
+    fn spare(&self) -> usize {
+        self.len
+    }
+}
+
+// 克隆出来的是一个空的节点池，缓存的节点不会被共享
+impl Clone for NodePool {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for NodePool {
+    fn drop(&mut self) {
+        self.shrink_to_fit();
+    }
+}
+
+// 空闲节点只被节点池自己持有
+unsafe impl Send for NodePool {}
+unsafe impl Sync for NodePool {}
+
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T> LinkedList<T, NodePool> {
+    // 预先在节点池中分配capacity个节点
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut list = Self::new_in(NodePool::new());
+        list.reserve(capacity);
+        list
+    }
+}
+
+impl<T, A: NodeAlloc> LinkedList<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         Self {
             front: None,
             back: None,
             len: 0,
+            alloc,
             _boo: PhantomData,
         }
     }
 
+    // 用一个已经摘下来的节点构造链表
+    fn from_node(node: NonNull<Node<T>>) -> Self {
+        let mut list = Self::default();
+        list.front = Some(node);
+        list.back = Some(node);
+        list.len = 1;
+        list
+    }
+
+    // 把所有节点转移到一个新链表中，分配器（以及缓存的空闲节点）留在原链表
+    fn take_nodes(&mut self) -> Self {
+        let mut list = Self::default();
+        list.front = self.front.take();
+        list.back = self.back.take();
+        list.len = mem::replace(&mut self.len, 0);
+        list
+    }
+
+    fn alloc_node(&mut self, elem: T) -> NonNull<Node<T>> {
+        let node = self.alloc.allocate(Layout::new::<Node<T>>()).cast::<Node<T>>();
+        unsafe {
+            node.as_ptr().write(Node {
+                front: None,
+                back: None,
+                elem,
+            });
+        }
+        node
+    }
+
+    // 取走节点中的元素，再把节点还给分配器
+    unsafe fn free_node(&mut self, node: NonNull<Node<T>>) -> T {
+        let elem = ptr::read(&(*node.as_ptr()).elem);
+        self.alloc.deallocate(node.cast(), Layout::new::<Node<T>>());
+        elem
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    // 不再分配内存的情况下最多能容纳的元素个数
+    pub fn capacity(&self) -> usize {
+        self.len + self.alloc.spare()
+    }
+
+    // 让分配器预留至少additional个空闲节点
+    pub fn reserve(&mut self, additional: usize) {
+        self.alloc.reserve(Layout::new::<Node<T>>(), additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.alloc.shrink_to_fit();
+    }
+
     pub fn front(&self) -> Option<&T> {
         unsafe {
             // self.front.map(|node| &(*node.as_ptr()).elem)
@@ -82,13 +317,9 @@ impl<T> LinkedList<T> {
     }
 
     pub fn push_front(&mut self, elem: T) {
+        // 通过分配器在堆上分配Node的空间
+        let new = self.alloc_node(elem);
         unsafe {
-            // 利用Box智能分配Node在堆上的空间
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                front: None,
-                back: None,
-                elem,
-            })));
             // 如果链表不为空，重新设置新旧链表头的关系
             if let Some(old) = self.front {
                 (*old.as_ptr()).front = Some(new);
@@ -105,13 +336,8 @@ impl<T> LinkedList<T> {
 
     // push_front的镜像操作
     pub fn push_back(&mut self, elem: T) {
+        let new = self.alloc_node(elem);
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                front: None,
-                back: None,
-                elem,
-            })));
-
             if let Some(old) = self.back {
                 (*old.as_ptr()).back = Some(new);
                 (*new.as_ptr()).front = Some(old);
@@ -126,12 +352,8 @@ impl<T> LinkedList<T> {
     pub fn pop_front(&mut self) -> Option<T> {
         unsafe {
             self.front.map(|node| {
-                // 利用Box在不需要的时候自动释放
-                let boxed_node = Box::from_raw(node.as_ptr());
-                let result = boxed_node.elem;
-
                 // 重新设置链表头
-                self.front = boxed_node.back;
+                self.front = (*node.as_ptr()).back;
                 if let Some(new) = self.front {
                     (*new.as_ptr()).front = None;
                 } else {
@@ -140,8 +362,8 @@ impl<T> LinkedList<T> {
                 }
 
                 self.len -= 1;
-                result
-                // 当box中的 T 所有权转移了，box就会在这里被释放
+                // 取走 T 的所有权，节点还给分配器
+                self.free_node(node)
             })
         }
     }
@@ -150,10 +372,7 @@ impl<T> LinkedList<T> {
     pub fn pop_back(&mut self) -> Option<T> {
         unsafe {
             self.back.map(|node| {
-                let boxed_node = Box::from_raw(node.as_ptr());
-                let result = boxed_node.elem;
-
-                self.back = boxed_node.front;
+                self.back = (*node.as_ptr()).front;
                 if let Some(new) = self.back {
                     (*new.as_ptr()).back = None;
                 } else {
@@ -161,7 +380,7 @@ impl<T> LinkedList<T> {
                 }
 
                 self.len -= 1;
-                result
+                self.free_node(node)
             })
         }
     }
@@ -179,6 +398,84 @@ impl<T> LinkedList<T> {
         while let Some(_) = self.pop_front() { }
     }
 
+    // 头尾节点的句柄，配合下面的*_unchecked方法使用
+    pub fn front_handle(&self) -> Option<Handle<T>> {
+        self.front.map(Handle)
+    }
+
+    pub fn back_handle(&self) -> Option<Handle<T>> {
+        self.back.map(Handle)
+    }
+
+    /// 通过句柄直接访问节点中的元素
+    ///
+    /// # Safety
+    ///
+    /// `handle`必须来自这个链表，并且它指向的节点还没有被删除。
+    pub unsafe fn get_unchecked(&self, handle: Handle<T>) -> &T {
+        &(*handle.0.as_ptr()).elem
+    }
+
+    /// `get_unchecked`的可变版本
+    ///
+    /// # Safety
+    ///
+    /// `handle`必须来自这个链表，并且它指向的节点还没有被删除。
+    pub unsafe fn get_unchecked_mut(&mut self, handle: Handle<T>) -> &mut T {
+        &mut (*handle.0.as_ptr()).elem
+    }
+
+    /// 把节点摘下来再接到链表头部，不重新分配
+    ///
+    /// # Safety
+    ///
+    /// `handle`必须来自这个链表，并且它指向的节点还没有被删除。
+    pub unsafe fn move_to_front_unchecked(&mut self, handle: Handle<T>) {
+        let node = handle.0;
+        if self.front == Some(node) {
+            return;
+        }
+        unlink(&NodeOps::new(), node, &mut self.front, &mut self.back);
+        if let Some(old) = self.front {
+            (*old.as_ptr()).front = Some(node);
+            (*node.as_ptr()).back = Some(old);
+        } else {
+            self.back = Some(node);
+        }
+        self.front = Some(node);
+    }
+
+    /// `move_to_front_unchecked`的镜像操作
+    ///
+    /// # Safety
+    ///
+    /// `handle`必须来自这个链表，并且它指向的节点还没有被删除。
+    pub unsafe fn move_to_back_unchecked(&mut self, handle: Handle<T>) {
+        let node = handle.0;
+        if self.back == Some(node) {
+            return;
+        }
+        unlink(&NodeOps::new(), node, &mut self.front, &mut self.back);
+        if let Some(old) = self.back {
+            (*old.as_ptr()).back = Some(node);
+            (*node.as_ptr()).front = Some(old);
+        } else {
+            self.front = Some(node);
+        }
+        self.back = Some(node);
+    }
+
+    /// 删除节点并返回其中的元素，之后这个句柄就失效了
+    ///
+    /// # Safety
+    ///
+    /// `handle`必须来自这个链表，并且它指向的节点还没有被删除。
+    pub unsafe fn remove_unchecked(&mut self, handle: Handle<T>) -> T {
+        unlink(&NodeOps::new(), handle.0, &mut self.front, &mut self.back);
+        self.len -= 1;
+        self.free_node(handle.0)
+    }
+
     pub fn iter(&self) -> Iter<T> {
         Iter {
             front: self.front,
@@ -197,32 +494,514 @@ impl<T> LinkedList<T> {
         }
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
+    pub fn into_iter(self) -> IntoIter<T, A> {
         IntoIter {
             list: self
         }
     }
 
+    // 只读cursor，指向链表的第一个节点，链表为空时指向幽灵节点
+    pub fn cursor_front(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.front,
+            list: self,
+            index: if self.front.is_some() { Some(0) } else { None },
+        }
+    }
+
+    // 只读cursor，指向链表的最后一个节点
+    pub fn cursor_back(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.back,
+            list: self,
+            index: self.len.checked_sub(1),
+        }
+    }
+
     // cursor
-    pub fn cursor_mut(&mut self) -> CursorMut<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<T, A> {
         CursorMut {
             cur: None,
             list: self,
             index: None,
         }
     }
+
+    // 返回指向第at个节点的cursor，at == len 时指向幽灵节点
+    // 从离at更近的一端开始移动，最多走 len / 2 步
+    fn cursor_at(&mut self, at: usize) -> CursorMut<'_, T, A> {
+        let len = self.len;
+        let mut cursor = self.cursor_mut();
+        if at <= len / 2 {
+            for _ in 0..=at {
+                cursor.move_next();
+            }
+        } else {
+            for _ in at..len {
+                cursor.move_prev();
+            }
+        }
+        cursor
+    }
+
+    // 整体操作，全部基于cursor的切割和拼接实现
+
+    // 把other的所有节点接到链表尾部，O(1)，other变为空链表
+    pub fn append(&mut self, other: &mut Self) {
+        // 幽灵节点的splice_before就是接到尾部
+        let nodes = other.take_nodes();
+        self.cursor_mut().splice_before(nodes);
+    }
+
+    // 在at处切开，返回 [at, len) 的部分，自身保留 [0, at)
+    pub fn split_off(&mut self, at: usize) -> LinkedList<T, A> {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            return self.take_nodes();
+        }
+        self.cursor_at(at - 1).split_after()
+    }
+
+    // 在at处插入元素，原来at及之后的元素往后移
+    pub fn insert(&mut self, at: usize, elem: T) {
+        assert!(at <= self.len, "Cannot insert at a nonexistent index");
+        self.cursor_at(at).insert_before(elem);
+    }
+
+    // 删除at处的元素并返回
+    pub fn remove(&mut self, at: usize) -> T {
+        assert!(at < self.len, "Cannot remove at an index outside of the list bounds");
+        self.cursor_at(at).remove_current().unwrap()
+    }
+
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq<T>,
+    {
+        self.iter().any(|e| e == x)
+    }
+
+    // 只保留f返回true的元素，按从前到后的顺序访问每个元素一次
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        while let Some(elem) = cursor.current() {
+            if f(elem) {
+                cursor.move_next();
+            } else {
+                // 删除后cursor自动指向下一个节点
+                cursor.remove_current();
+            }
+        }
+    }
+
+    // 惰性地删除pred返回true的元素，每次next返回一个被删除的元素
+    // 迭代器提前drop时，还没访问到的元素留在链表中
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        ExtractIf { cursor, pred }
+    }
+
+    // 删除range范围内的元素，返回由这些元素组成的迭代器
+    // 节点在调用时就已经切下来了，即使迭代器没有被消费，range内的元素也会被删除
+    pub fn drain<R>(&mut self, range: R) -> IntoIter<T, A>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("drain start overflow"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("drain end overflow"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end, "drain start is greater than end");
+        assert!(end <= self.len, "drain end is out of bounds");
+
+        // 先切下尾部，再切下range，最后把尾部接回来
+        let mut tail = self.split_off(end);
+        let drained = self.split_off(start);
+        self.append(&mut tail);
+        drained.into_iter()
+    }
+
+    // 排序，直接重连原有的节点，不会重新分配内存
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    // 归并排序本身就是稳定的，所以这里直接复用sort_by
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_by(compare);
+    }
+
+    // 自底向上的归并排序：每一轮把相邻的两段长度为width的有序段合并，width翻倍，
+    // 直到某一轮只发生一次合并。排序过程中只维护back指针（当作单链表），最后再统一修复front指针
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.len < 2 {
+            return;
+        }
+
+        // 先把节点从链表上摘下来交给guard，排序结束或者compare发生panic时由guard重新接回链表
+        let len = self.len;
+        let rest = self.front.take();
+        self.back = None;
+        self.len = 0;
+        let mut guard = MergeGuard::new(self, len);
+        guard.rest = rest;
+
+        unsafe {
+            let mut width = 1;
+            loop {
+                let mut merges = 0;
+                while guard.rest.is_some() {
+                    guard.a = guard.rest;
+                    guard.b = split_chain(guard.a, width);
+                    guard.rest = split_chain(guard.b, width);
+                    guard.merge(&mut compare);
+                    merges += 1;
+                }
+                if merges <= 1 {
+                    break;
+                }
+                // 这一轮合并出来的链作为下一轮的输入
+                guard.rest = guard.head.take();
+                guard.tail = None;
+                width *= 2;
+            }
+        }
+    }
+
+    // 合并两个已经有序的链表，other变为空链表，相等的元素self中的排在前面
+    pub fn merge(&mut self, other: &mut Self)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, T::cmp);
+    }
+
+    pub fn merge_by<F>(&mut self, other: &mut Self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let len = self.len + other.len;
+        // 和sort_by一样，先把两个链表都清空，compare发生panic时所有节点都回到self中
+        let a = self.front.take();
+        let b = other.front.take();
+        self.back = None;
+        other.back = None;
+        self.len = 0;
+        other.len = 0;
+
+        let mut guard = MergeGuard::new(self, len);
+        guard.a = a;
+        guard.b = b;
+        unsafe { guard.merge(&mut compare) };
+    }
+
+    // 根据back指针重新设置每个节点的front指针以及链表的头尾和长度
+    unsafe fn relink_chain(&mut self, head: Link<T>, len: usize) {
+        let mut prev: Link<T> = None;
+        let mut cur = head;
+        while let Some(node) = cur {
+            (*node.as_ptr()).front = prev;
+            prev = cur;
+            cur = (*node.as_ptr()).back;
+        }
+        self.front = head;
+        self.back = prev;
+        self.len = len;
+    }
+
+    // 删除连续重复的元素，只保留第一个
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    // same_bucket(a, b)中a是后一个元素，b是前一个保留下来的元素，返回true时删除a
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        while let Some(cur) = cursor.cur {
+            unsafe {
+                let next = match (*cur.as_ptr()).back {
+                    Some(next) => next,
+                    None => break,
+                };
+                if same_bucket(&mut (*next.as_ptr()).elem, &mut (*cur.as_ptr()).elem) {
+                    // 删除下一个节点后回到当前节点，继续和新的下一个节点比较
+                    cursor.move_next();
+                    cursor.remove_current();
+                    cursor.move_prev();
+                } else {
+                    cursor.move_next();
+                }
+            }
+        }
+    }
+}
+
+// 读写双向链表节点的前后指针
+// LinkedList和unrolled::UnrolledList的Node直接用字段保存指针，intrusive::IntrusiveList的指针保存在用户结构体里，
+// 通过这个trait它们可以共用同一份摘除节点的逻辑
+pub(crate) trait LinkOps {
+    type Node;
+
+    unsafe fn front(&self, node: NonNull<Self::Node>) -> Option<NonNull<Self::Node>>;
+    unsafe fn back(&self, node: NonNull<Self::Node>) -> Option<NonNull<Self::Node>>;
+    unsafe fn set_front(&self, node: NonNull<Self::Node>, link: Option<NonNull<Self::Node>>);
+    unsafe fn set_back(&self, node: NonNull<Self::Node>, link: Option<NonNull<Self::Node>>);
+}
+
+type OpsLink<O> = Option<NonNull<<O as LinkOps>::Node>>;
+
+struct NodeOps<T>(PhantomData<T>);
+
+impl<T> NodeOps<T> {
+    fn new() -> Self {
+        NodeOps(PhantomData)
+    }
+}
+
+impl<T> LinkOps for NodeOps<T> {
+    type Node = Node<T>;
+
+    unsafe fn front(&self, node: NonNull<Node<T>>) -> Link<T> {
+        (*node.as_ptr()).front
+    }
+
+    unsafe fn back(&self, node: NonNull<Node<T>>) -> Link<T> {
+        (*node.as_ptr()).back
+    }
+
+    unsafe fn set_front(&self, node: NonNull<Node<T>>, link: Link<T>) {
+        (*node.as_ptr()).front = link;
+    }
+
+    unsafe fn set_back(&self, node: NonNull<Node<T>>, link: Link<T>) {
+        (*node.as_ptr()).back = link;
+    }
+}
+
+// 把node从以front、back为头尾的链表上摘下来，前后两个节点（或者链表的头尾）直接相连
+// 摘下来的节点前后指针都置为None，返回它原来的前后节点
+pub(crate) unsafe fn unlink<O: LinkOps>(
+    ops: &O,
+    node: NonNull<O::Node>,
+    front: &mut OpsLink<O>,
+    back: &mut OpsLink<O>,
+) -> (OpsLink<O>, OpsLink<O>) {
+    let prev = ops.front(node);
+    let next = ops.back(node);
+    ops.set_front(node, None);
+    ops.set_back(node, None);
+
+    if let Some(prev) = prev {
+        ops.set_back(prev, next);
+    } else {
+        *front = next;
+    }
+
+    if let Some(next) = next {
+        ops.set_front(next, prev);
+    } else {
+        *back = prev;
+    }
+
+    (prev, next)
+}
+
+// 从head开始数len个节点，在此处切开，返回剩下部分的头节点
+unsafe fn split_chain<T>(head: Link<T>, len: usize) -> Link<T> {
+    let mut cur = head;
+    for _ in 1..len {
+        match cur {
+            Some(node) => cur = (*node.as_ptr()).back,
+            None => return None,
+        }
+    }
+    cur.and_then(|node| (*node.as_ptr()).back.take())
 }
 
-impl<T> Default for LinkedList<T>  {
+// 排序和合并时还没有接回链表的所有节点，都是以back指针相连、以None结尾的链。
+// drop时按 已合并部分 -> a -> b -> rest 的顺序把它们首尾相接，再重建front指针还给链表，
+// 所以即使compare发生panic，链表也不会丢失节点，只是顺序不确定
+struct MergeGuard<'a, T, A: NodeAlloc> {
+    list: &'a mut LinkedList<T, A>,
+    len: usize,
+    // 已经合并好的部分
+    head: Link<T>,
+    tail: Link<T>,
+    // 正在合并的两条链
+    a: Link<T>,
+    b: Link<T>,
+    // 还没有处理的部分
+    rest: Link<T>,
+}
+
+impl<'a, T, A: NodeAlloc> MergeGuard<'a, T, A> {
+    fn new(list: &'a mut LinkedList<T, A>, len: usize) -> Self {
+        MergeGuard {
+            list,
+            len,
+            head: None,
+            tail: None,
+            a: None,
+            b: None,
+            rest: None,
+        }
+    }
+
+    // 把一整条链接到已合并部分的尾部
+    unsafe fn push_chain(&mut self, chain: Link<T>) {
+        let Some(mut last) = chain else { return };
+        if let Some(tail) = self.tail {
+            (*tail.as_ptr()).back = chain;
+        } else {
+            self.head = chain;
+        }
+        while let Some(next) = (*last.as_ptr()).back {
+            last = next;
+        }
+        self.tail = Some(last);
+    }
+
+    // 把a和b两条有序链合并到已合并部分的尾部，相等时优先取a中的节点，保证排序的稳定性
+    // 节点只有在compare返回之后才从a或b中取出，compare发生panic时不会有节点处于中间状态
+    unsafe fn merge<F>(&mut self, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        while let (Some(x), Some(y)) = (self.a, self.b) {
+            let node = if compare(&(*y.as_ptr()).elem, &(*x.as_ptr()).elem) == Ordering::Less {
+                self.b = (*y.as_ptr()).back;
+                y
+            } else {
+                self.a = (*x.as_ptr()).back;
+                x
+            };
+            if let Some(tail) = self.tail {
+                (*tail.as_ptr()).back = Some(node);
+            } else {
+                self.head = Some(node);
+            }
+            self.tail = Some(node);
+        }
+        // 只剩下一条链，直接接到尾部
+        let rest = self.a.take().or(self.b.take());
+        self.push_chain(rest);
+        if let Some(tail) = self.tail {
+            (*tail.as_ptr()).back = None;
+        }
+    }
+}
+
+impl<T, A: NodeAlloc> Drop for MergeGuard<'_, T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            // 最后一个已合并节点的back可能还指向a或b中的节点，先断开再接上剩下的链
+            if let Some(tail) = self.tail {
+                (*tail.as_ptr()).back = None;
+            }
+            let (a, b, rest) = (self.a.take(), self.b.take(), self.rest.take());
+            self.push_chain(a);
+            self.push_chain(b);
+            self.push_chain(rest);
+            self.list.relink_chain(self.head, self.len);
+        }
+    }
+}
+
+impl<T> Handle<T> {
+    /// 节点中元素的裸指针，不会创建对元素的引用。
+    /// 调用者可以只访问元素的某个字段，不影响已经借出去的其他字段；节点被删除之后指针失效。
+    pub fn as_ptr(self) -> *mut T {
+        unsafe { ptr::addr_of_mut!((*self.0.as_ptr()).elem) }
+    }
+}
+
+// 句柄只是一个指针，不要求T: Clone也可以复制
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.0).finish()
+    }
+}
+
+impl<T, A: NodeAlloc> Default for LinkedList<T, A> {
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
 // 实现clone特征
-impl<T: Clone> Clone for LinkedList<T>  {
+impl<T: Clone, A: NodeAlloc + Clone> Clone for LinkedList<T, A> {
     fn clone(&self) -> Self {
-        let mut new_list = Self::new();
+        let mut new_list = Self::new_in(self.alloc.clone());
         for item in self {
             new_list.push_back(item.clone());
         }
@@ -231,7 +1010,7 @@ impl<T: Clone> Clone for LinkedList<T>  {
 }
 
 // 实现extend特征，就是批量push
-impl<T> Extend<T> for LinkedList<T> {
+impl<T, A: NodeAlloc> Extend<T> for LinkedList<T, A> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
         for item in iter {
             self.push_back(item);
@@ -240,23 +1019,23 @@ impl<T> Extend<T> for LinkedList<T> {
 }
 
 // 新建一个LinkedList的迭代器，并把元素添加进去
-impl<T> FromIterator<T> for LinkedList<T> {
+impl<T, A: NodeAlloc> FromIterator<T> for LinkedList<T, A> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut list = Self::new();
+        let mut list = Self::default();
         list.extend(iter);
         list
     }
 }
 
 // 实现debug特征
-impl<T: Debug> Debug for LinkedList<T> {
+impl<T: Debug, A: NodeAlloc> Debug for LinkedList<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self).finish()
     }
 }
 
 // 实现Eq特征，主要用来判断两个链表是否相等
-impl<T: PartialEq> PartialEq for LinkedList<T> {
+impl<T: PartialEq, A: NodeAlloc> PartialEq for LinkedList<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().eq(other)
     }
@@ -266,16 +1045,16 @@ impl<T: PartialEq> PartialEq for LinkedList<T> {
     }
 }
 
-impl<T: Eq> Eq for LinkedList<T> { }
+impl<T: Eq, A: NodeAlloc> Eq for LinkedList<T, A> { }
 
 // 重载操作符
-impl<T: PartialOrd> PartialOrd for LinkedList<T> {
+impl<T: PartialOrd, A: NodeAlloc> PartialOrd for LinkedList<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.iter().partial_cmp(other)
     }
 }
 
-impl<T: Ord> Ord for LinkedList<T> {
+impl<T: Ord, A: NodeAlloc> Ord for LinkedList<T, A> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.iter().cmp(other)
     }
@@ -283,7 +1062,7 @@ impl<T: Ord> Ord for LinkedList<T> {
 
 
 // 获取当前链表的hash值
-impl<T: Hash> Hash for LinkedList<T> {
+impl<T: Hash, A: NodeAlloc> Hash for LinkedList<T, A> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.len().hash(state);
         for item in self {
@@ -292,8 +1071,24 @@ impl<T: Hash> Hash for LinkedList<T> {
     }
 }
 
+// 序列化为从front到back的序列
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: NodeAlloc> serde::Serialize for LinkedList<T, A> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, A: NodeAlloc> serde::Deserialize<'de> for LinkedList<T, A> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        Ok(elems.into_iter().collect())
+    }
+}
+
 /// Iter
-impl<'a, T> IntoIterator for &'a LinkedList<T> {
+impl<'a, T, A: NodeAlloc> IntoIterator for &'a LinkedList<T, A> {
     type IntoIter = Iter<'a, T>;
     type Item = &'a T;
 
@@ -345,7 +1140,7 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T>  {
 }
 
 /// IterMut
-impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+impl<'a, T, A: NodeAlloc> IntoIterator for &'a mut LinkedList<T, A> {
     type IntoIter = IterMut<'a, T>;
     type Item = &'a mut T;
 
@@ -398,8 +1193,8 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
 }
 
 /// IntoIter
-impl<T> IntoIterator for LinkedList<T> {
-    type IntoIter = IntoIter<T>;
+impl<T, A: NodeAlloc> IntoIterator for LinkedList<T, A> {
+    type IntoIter = IntoIter<T, A>;
     type Item = T;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -407,7 +1202,7 @@ impl<T> IntoIterator for LinkedList<T> {
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: NodeAlloc> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -419,21 +1214,21 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: NodeAlloc> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.list.pop_back()
     }
 }
 
-impl<T> ExactSizeIterator for IntoIter<T> {
+impl<T, A: NodeAlloc> ExactSizeIterator for IntoIter<T, A> {
     fn len(&self) -> usize {
         self.list.len
     }
 }
 
 // implement send and sync trait
-unsafe impl<T: Send> Send for LinkedList<T> {}
-unsafe impl<T: Sync> Sync for LinkedList<T> {}
+unsafe impl<T: Send, A: NodeAlloc + Send> Send for LinkedList<T, A> {}
+unsafe impl<T: Sync, A: NodeAlloc + Sync> Sync for LinkedList<T, A> {}
 
 unsafe impl<'a, T: Send> Send for Iter<'a, T> {}
 unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
@@ -441,13 +1236,112 @@ unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
 unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
 unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
 
+// Cursor只持有共享引用，和&LinkedList一样只要求T: Sync
+unsafe impl<'a, T: Sync, A: NodeAlloc + Sync> Send for Cursor<'a, T, A> {}
+unsafe impl<'a, T: Sync, A: NodeAlloc + Sync> Sync for Cursor<'a, T, A> {}
+
+// 手动实现Clone，避免derive要求T: Clone
+impl<'a, T, A: NodeAlloc> Clone for Cursor<'a, T, A> {
+    fn clone(&self) -> Self {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+}
+
+// Cursor是CursorMut的只读版本，多个Cursor可以同时借用同一个链表
+impl<'a, T, A: NodeAlloc> Cursor<'a, T, A> {
+    // 返回当前索引
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    // cur 向后移动操作，逻辑和CursorMut::move_next相同
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).back;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.front;
+            self.index = Some(0)
+        }
+    }
+
+    // move_next的镜像操作
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).front;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.back;
+            self.index = Some(self.list.len() - 1)
+        }
+    }
+
+    // 返回的引用生命周期和链表相同，而不是和cursor相同
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe {
+            self.cur.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+
+    // 获取下一个元素
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe {
+            let next = if let Some(cur) = self.cur {
+                (*cur.as_ptr()).back
+            } else {
+                self.list.front
+            };
+
+            next.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+
+    // peek_next镜像操作
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe {
+            let prev = if let Some(cur) = self.cur {
+                (*cur.as_ptr()).front
+            } else {
+                self.list.back
+            };
+
+            prev.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+}
+
 // cursor类似迭代器，但是可以自用的前后移动，cursorMut在自由移动的同时，可以修改链表
-impl<'a, T> CursorMut<'a, T>  {
+impl<'a, T, A: NodeAlloc> CursorMut<'a, T, A> {
     // 返回当前索引
     pub fn index(&self) -> Option<usize> {
         self.index
     }
 
+    // 以只读cursor的形式查看当前位置，借用期间不能再修改链表
+    pub fn as_cursor(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
     // cur 向后移动操作
     pub fn move_next(&mut self) {
         if let Some(cur) = self.cur {
@@ -488,7 +1382,9 @@ impl<'a, T> CursorMut<'a, T>  {
         }
     }
 
-    pub fn current(&self) -> Option<&mut T> {
+    // 需要&mut self，保证同一时间只能从cursor拿到一个可变引用
+    // 返回的引用和这次借用绑定，在它失效之前cursor不能移动或修改链表
+    pub fn current(&mut self) -> Option<&mut T> {
         unsafe {
             self.cur.map(|node| &mut (*node.as_ptr()).elem)
         }
@@ -523,7 +1419,7 @@ impl<'a, T> CursorMut<'a, T>  {
     /// 实现切割
     
     // 以当前光标作为前链表的front，前一个节点为后链表的back，返回前链表
-    pub fn split_before(&mut self) -> LinkedList<T> {
+    pub fn split_before(&mut self) -> LinkedList<T, A> {
         if let Some(cur) = self.cur {
             unsafe {
                 // 保存当前光标状态
@@ -539,7 +1435,8 @@ impl<'a, T> CursorMut<'a, T>  {
 
                 // 输出链表的基本信息
                 let output_len = old_len - new_len;
-                let output_front = self.list.front;
+                // cursor指向链表头时前面没有节点，输出空链表
+                let output_front = if prev.is_some() { self.list.front } else { None };
                 let output_back = prev;
                 
                 // 将链表切割，两边的头尾节点都需要重新赋值
@@ -557,17 +1454,18 @@ impl<'a, T> CursorMut<'a, T>  {
                     front: output_front,
                     back: output_back,
                     len: output_len,
+                    alloc: A::default(),
                     _boo: PhantomData,
                 }
             }
         } else {
-            // 如果当前光标为None，replace 空链表，返回原来的链表
-            std::mem::replace(self.list, LinkedList::new())
+            // 如果当前光标为None，把所有节点转移出去，返回原来的链表
+            self.list.take_nodes()
         }
     }
 
     // 以当前光标作为前链表的back，后一个节点为后链表的front，返回后链表
-    pub fn split_after(&mut self) -> LinkedList<T> {
+    pub fn split_after(&mut self) -> LinkedList<T, A> {
         
         if let Some(cur) = self.cur {
             unsafe {
@@ -580,11 +1478,12 @@ impl<'a, T> CursorMut<'a, T>  {
                 let new_len = old_idx + 1;
                 let new_front = self.list.front;
                 let new_back = self.cur;
-                let new_idx = Some(old_len - 1);
+                let new_idx = Some(old_idx);
 
                 let output_len = old_len - new_len;
                 let output_front = next;
-                let output_back = self.list.back;
+                // cursor指向链表尾时后面没有节点，输出空链表
+                let output_back = if next.is_some() { self.list.back } else { None };
 
                 if let Some(next) = next {
                     (*cur.as_ptr()).back = None;
@@ -600,16 +1499,17 @@ impl<'a, T> CursorMut<'a, T>  {
                     front: output_front,
                     back: output_back,
                     len: output_len,
+                    alloc: A::default(),
                     _boo: PhantomData,
                 }
             }
         } else {
-            // 如果当前光标为None，replace 空链表，返回原来的链表
-            std::mem::replace(self.list, LinkedList::new())
+            // 如果当前光标为None，把所有节点转移出去，返回原来的链表
+            self.list.take_nodes()
         }
     }
 
-    pub fn splice_before(&mut self, mut input: LinkedList<T>) {
+    pub fn splice_before(&mut self, mut input: LinkedList<T, A>) {
         unsafe {
             if input.is_empty() {
                 // 如果输入链表为空，不做任何事情
@@ -653,8 +1553,9 @@ impl<'a, T> CursorMut<'a, T>  {
                 (*in_front.as_ptr()).front = Some(back);
                 self.list.back = Some(in_back);
             } else {
-                // 原链表为空，直接替换
-                std::mem::swap(self.list, &mut input);
+                // 原链表为空，直接接管输入链表的节点，分配器保持不变
+                self.list.front = input.front.take();
+                self.list.back = input.back.take();
             }
 
             // 修改原链表长度
@@ -664,7 +1565,7 @@ impl<'a, T> CursorMut<'a, T>  {
     }
 
     // splice_after 类似splice_before
-    pub fn splice_after(&mut self, mut input: LinkedList<T>) {
+    pub fn splice_after(&mut self, mut input: LinkedList<T, A>) {
         unsafe {
 
             if input.is_empty() {
@@ -696,20 +1597,91 @@ impl<'a, T> CursorMut<'a, T>  {
                 (*in_back.as_ptr()).back = Some(front);
                 self.list.front = Some(in_front);
             } else {
-                // 原链表为空，直接替换
-                std::mem::swap(self.list, &mut input);
+                // 原链表为空，直接接管输入链表的节点，分配器保持不变
+                self.list.front = input.front.take();
+                self.list.back = input.back.take();
             }
 
             // 改变链表长度
             self.list.len += input.len;
             input.len = 0;
-        }        
+        }
+    }
+
+    // 插入与删除
+
+    // 在cursor前插入一个元素，cursor指向幽灵节点时插入到链表尾部
+    pub fn insert_before(&mut self, elem: T) {
+        // 用链表自己的分配器分配节点，构造只有一个节点的链表，复用splice_before的重连逻辑和index处理
+        let node = self.list.alloc_node(elem);
+        self.splice_before(LinkedList::from_node(node));
+    }
+
+    // 在cursor后插入一个元素，cursor指向幽灵节点时插入到链表头部
+    pub fn insert_after(&mut self, elem: T) {
+        let node = self.list.alloc_node(elem);
+        self.splice_after(LinkedList::from_node(node));
+    }
+
+    // 删除当前节点并返回元素，cursor移动到下一个节点，index保持不变
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.unlink_current()?;
+        // 取走元素后把节点还给链表的分配器
+        unsafe { Some(self.list.free_node(node)) }
+    }
+
+    // 和remove_current一样，但是不释放节点，而是把它作为只有一个元素的链表返回
+    pub fn remove_current_as_list(&mut self) -> Option<LinkedList<T, A>> {
+        let node = self.unlink_current()?;
+        Some(LinkedList::from_node(node))
+    }
+
+    // 把当前节点从链表中摘出来，前后两个节点（或者链表的头尾）直接相连
+    // 返回的节点前后指针都为None，由调用者负责释放
+    fn unlink_current(&mut self) -> Link<T> {
+        let cur = self.cur?;
+        unsafe {
+            let (_, next) = unlink(&NodeOps::new(), cur, &mut self.list.front, &mut self.list.back);
+            if next.is_none() {
+                // 删除的是最后一个节点，cursor移动到幽灵节点
+                self.index = None;
+            }
+
+            self.list.len -= 1;
+            self.cur = next;
+        }
+        Some(cur)
     }
+}
 
+impl<'a, T, F, A: NodeAlloc> Iterator for ExtractIf<'a, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // cursor走到幽灵节点时，整个链表都访问过了
+        while let Some(elem) = self.cursor.current() {
+            if (self.pred)(elem) {
+                // 删除后cursor指向下一个节点，下次从这里继续
+                return self.cursor.remove_current();
+            }
+            self.cursor.move_next();
+        }
+        None
+    }
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match self.cursor.index() {
+            Some(index) => self.cursor.list.len - index,
+            None => 0,
+        };
+        (0, Some(remaining))
+    }
 }
 
-impl<T> Drop for LinkedList<T> {
+impl<T, A: NodeAlloc> Drop for LinkedList<T, A> {
     fn drop(&mut self) {
         // 弹出node，直达为空
         while let Some(_) = self.pop_front() { }
@@ -718,7 +1690,10 @@ impl<T> Drop for LinkedList<T> {
 
 #[cfg(test)]
 mod test {
-    use super::LinkedList;
+    use super::{Global, LinkedList, NodeAlloc, NodePool};
+    use std::alloc::Layout;
+    use std::cell::Cell;
+    use std::ptr::NonNull;
 
     fn generate_test() -> LinkedList<i32> {
         list_from(&[0, 1, 2, 3, 4, 5, 6])
@@ -1028,6 +2003,62 @@ mod test {
         assert_eq!(cursor.index(), Some(4));
     }
 
+    #[test]
+    fn test_cursor_read_only() {
+        let m: LinkedList<u32> = (1..=6).collect();
+
+        // 多个只读cursor可以同时存在
+        let mut front = m.cursor_front();
+        let mut back = m.cursor_back();
+        assert_eq!(front.current(), Some(&1));
+        assert_eq!(front.index(), Some(0));
+        assert_eq!(back.current(), Some(&6));
+        assert_eq!(back.index(), Some(5));
+
+        assert_eq!(front.peek_prev(), None);
+        assert_eq!(front.peek_next(), Some(&2));
+        front.move_prev();
+        assert_eq!(front.current(), None);
+        assert_eq!(front.index(), None);
+        assert_eq!(front.peek_next(), Some(&1));
+        assert_eq!(front.peek_prev(), Some(&6));
+
+        let saved = back.clone();
+        back.move_next();
+        assert_eq!(back.current(), None);
+        back.move_next();
+        back.move_next();
+        assert_eq!(back.current(), Some(&2));
+        assert_eq!(back.index(), Some(1));
+        assert_eq!(saved.current(), Some(&6));
+
+        // current 返回的引用可以比cursor活得更久
+        let elem = {
+            let cursor = m.cursor_front();
+            cursor.current()
+        };
+        assert_eq!(elem, Some(&1));
+
+        let empty: LinkedList<u32> = LinkedList::new();
+        let mut cursor = empty.cursor_front();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(empty.cursor_back().index(), None);
+
+        let mut m = m;
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let view = cursor.as_cursor();
+        assert_eq!(view.current(), Some(&2));
+        assert_eq!(view.index(), Some(1));
+        assert_eq!(view.peek_next(), Some(&3));
+        cursor.move_next();
+        assert_eq!(cursor.as_cursor().index(), Some(2));
+    }
+
     #[test]
     fn test_cursor_mut_insert() {
         let mut m: LinkedList<u32> = LinkedList::new();
@@ -1052,7 +2083,6 @@ mod test {
             &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
         );
 
-        /* remove_current not impl'd
         let mut cursor = m.cursor_mut();
         cursor.move_next();
         cursor.move_prev();
@@ -1068,7 +2098,6 @@ mod test {
         assert_eq!(cursor.remove_current(), Some(10));
         check_links(&m);
         assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 8, 2, 3, 4, 5, 6]);
-        */
 
         let mut m: LinkedList<u32> = LinkedList::new();
         m.extend([1, 8, 2, 3, 4, 5, 6]);
@@ -1089,7 +2118,7 @@ mod test {
         cursor.move_next();
         cursor.move_prev();
         let tmp = cursor.split_before();
-        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[]);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), Vec::<u32>::new());
         m = tmp;
         let mut cursor = m.cursor_mut();
         cursor.move_next();
@@ -1111,7 +2140,628 @@ mod test {
         );
     }
 
-    fn check_links<T: Eq + std::fmt::Debug>(list: &LinkedList<T>) {
+    #[test]
+    fn test_cursor_insert_remove() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.extend([1, 2, 3]);
+        let mut cursor = m.cursor_mut();
+
+        // 幽灵节点：insert_before插到尾部，insert_after插到头部
+        cursor.insert_before(4);
+        cursor.insert_after(0);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        assert!(cursor.remove_current_as_list().is_none());
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(1));
+        cursor.insert_before(10);
+        assert_eq!(cursor.index(), Some(2));
+        cursor.insert_after(11);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.peek_next(), Some(&mut 11));
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 10, 1, 11, 2, 3, 4]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        let tail = cursor.remove_current_as_list().unwrap();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        check_links(&tail);
+        assert_eq!(tail.into_iter().collect::<Vec<_>>(), &[4]);
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(10));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.remove_current(), Some(11));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.index(), Some(1));
+        check_links(&m);
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 2, 3]);
+
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.push_back(1);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(2);
+        check_links(&m);
+        assert_eq!(m.front(), Some(&2));
+        assert_eq!(m.back(), Some(&2));
+        assert_eq!(m.len(), 1);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut m: LinkedList<u32> = (0..3).collect();
+        let mut n: LinkedList<u32> = (3..6).collect();
+        m.append(&mut n);
+        check_links(&m);
+        assert!(n.is_empty());
+        assert_eq!(n.front(), None);
+        assert_eq!(m.len(), 6);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 1, 2, 3, 4, 5]);
+
+        // 空链表的两种情况
+        m.append(&mut n);
+        assert_eq!(m.len(), 6);
+        n.append(&mut m);
+        check_links(&n);
+        assert!(m.is_empty());
+        assert_eq!(n.len(), 6);
+        assert_eq!(n.back(), Some(&5));
+    }
+
+    #[test]
+    fn test_split_off() {
+        // 从前半段和后半段分别切开
+        for at in 0..=6 {
+            let mut m = generate_test();
+            let tail = m.split_off(at);
+            check_links(&m);
+            check_links(&tail);
+            assert_eq!(m.len(), at);
+            assert_eq!(tail.len(), 7 - at);
+            assert_eq!(m.into_iter().collect::<Vec<_>>(), (0..at as i32).collect::<Vec<_>>());
+            assert_eq!(tail.into_iter().collect::<Vec<_>>(), (at as i32..7).collect::<Vec<_>>());
+        }
+
+        let mut m: LinkedList<i32> = LinkedList::new();
+        assert!(m.split_off(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_off_out_of_bounds() {
+        let mut m = generate_test();
+        m.split_off(8);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        m.insert(0, 1);
+        m.insert(0, 0);
+        m.insert(2, 3);
+        m.insert(2, 2);
+        m.insert(4, 5);
+        m.insert(4, 4);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 1, 2, 3, 4, 5]);
+
+        assert_eq!(m.remove(4), 4);
+        assert_eq!(m.remove(1), 1);
+        assert_eq!(m.remove(3), 5);
+        assert_eq!(m.remove(0), 0);
+        check_links(&m);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_remove_out_of_bounds() {
+        let mut m = generate_test();
+        m.remove(7);
+    }
+
+    #[test]
+    fn test_contains() {
+        let m = generate_test();
+        assert!(m.contains(&0));
+        assert!(m.contains(&6));
+        assert!(!m.contains(&7));
+        assert!(!LinkedList::new().contains(&0));
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        m.retain(|x| x % 3 == 0);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 3, 6, 9]);
+
+        m.retain_mut(|x| {
+            *x += 1;
+            *x != 1
+        });
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[4, 7, 10]);
+
+        m.retain(|_| false);
+        assert!(m.is_empty());
+        assert_eq!(m.back(), None);
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let evens: Vec<_> = m.extract_if(|x| *x % 2 == 0).collect();
+        check_links(&m);
+        assert_eq!(evens, &[0, 2, 4, 6, 8]);
+        assert_eq!(m.len(), 5);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[1, 3, 5, 7, 9]);
+
+        // 提前drop，还没访问的元素留在链表中
+        {
+            let mut iter = m.extract_if(|x| {
+                *x *= 10;
+                *x > 20
+            });
+            assert_eq!(iter.size_hint(), (0, Some(5)));
+            assert_eq!(iter.next(), Some(30));
+            assert_eq!(iter.size_hint(), (0, Some(3)));
+        }
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[10, 5, 7, 9]);
+
+        // 删除所有元素，链表头尾也要正确重置
+        assert_eq!(m.extract_if(|_| true).count(), 4);
+        assert!(m.is_empty());
+        assert_eq!(m.front(), None);
+        assert_eq!(m.back(), None);
+        assert_eq!(m.extract_if(|_| true).next(), None);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut m = generate_test();
+        let drained: Vec<_> = m.drain(2..5).collect();
+        check_links(&m);
+        assert_eq!(drained, &[2, 3, 4]);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 1, 5, 6]);
+
+        // 不消费迭代器也会删除元素
+        drop(m.drain(..=0));
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[1, 5, 6]);
+
+        assert_eq!(m.drain(1..1).count(), 0);
+        assert_eq!(m.drain(2..).collect::<Vec<_>>(), &[6]);
+        assert_eq!(m.drain(..).rev().collect::<Vec<_>>(), &[5, 1]);
+        check_links(&m);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drain_out_of_bounds() {
+        let mut m = generate_test();
+        m.drain(5..8);
+    }
+
+    // 简单的线性同余生成器，保证测试结果可以复现
+    fn pseudo_random(n: usize, seed: u64) -> Vec<u32> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as u32 % 100
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sort() {
+        for len in [0, 1, 2, 3, 7, 8, 9, 100, 1000] {
+            let v = pseudo_random(len, len as u64);
+            let mut m: LinkedList<u32> = v.iter().copied().collect();
+            m.sort();
+            check_links(&m);
+            assert_eq!(m.len(), len);
+
+            let mut sorted = v.clone();
+            sorted.sort();
+            assert_eq!(m.iter().copied().collect::<Vec<_>>(), sorted);
+            assert_eq!(m.front(), sorted.first());
+            assert_eq!(m.back(), sorted.last());
+
+            m.sort_unstable_by(|a, b| b.cmp(a));
+            check_links(&m);
+            sorted.reverse();
+            assert_eq!(m.into_iter().collect::<Vec<_>>(), sorted);
+        }
+    }
+
+    #[test]
+    fn test_sort_stable() {
+        let keys = pseudo_random(200, 42);
+        let mut m: LinkedList<(u32, usize)> = keys.iter().map(|k| k % 10).zip(0..).collect();
+        m.sort_by_key(|&(k, _)| k);
+        check_links(&m);
+
+        // 相同key的元素保持原来的相对顺序
+        let v: Vec<_> = m.into_iter().collect();
+        for pair in v.windows(2) {
+            assert!(pair[0].0 < pair[1].0 || (pair[0].0 == pair[1].0 && pair[0].1 < pair[1].1));
+        }
+    }
+
+    #[test]
+    fn test_sort_panic() {
+        let mut m: LinkedList<u32> = pseudo_random(50, 7).into_iter().collect();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut count = 0;
+            m.sort_by(|a, b| {
+                count += 1;
+                assert!(count < 20);
+                a.cmp(b)
+            });
+        }));
+        assert!(result.is_err());
+        // panic后所有元素都还在链表中，只是顺序不确定
+        check_links(&m);
+        assert_eq!(m.len(), 50);
+        let mut v: Vec<_> = m.iter().copied().collect();
+        let mut expected = pseudo_random(50, 7);
+        v.sort();
+        expected.sort();
+        assert_eq!(v, expected);
+        m.push_back(1);
+        assert_eq!(m.len(), 51);
+    }
+
+    #[test]
+    fn test_merge_panic() {
+        let mut m: LinkedList<u32> = (0..20).map(|i| i * 2).collect();
+        let mut n: LinkedList<u32> = (0..20).map(|i| i * 2 + 1).collect();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut count = 0;
+            m.merge_by(&mut n, |a, b| {
+                count += 1;
+                assert!(count < 10);
+                a.cmp(b)
+            });
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+        assert!(n.is_empty());
+        let mut v: Vec<_> = m.iter().copied().collect();
+        v.sort();
+        assert_eq!(v, (0..40).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut m: LinkedList<(i32, char)> = [(1, 'a'), (3, 'a'), (5, 'a')].into_iter().collect();
+        let mut n: LinkedList<(i32, char)> = [(0, 'b'), (3, 'b'), (6, 'b'), (7, 'b')].into_iter().collect();
+        m.merge_by(&mut n, |a, b| a.0.cmp(&b.0));
+        check_links(&m);
+        assert!(n.is_empty());
+        assert_eq!(m.len(), 7);
+        assert_eq!(
+            m.iter().copied().collect::<Vec<_>>(),
+            &[(0, 'b'), (1, 'a'), (3, 'a'), (3, 'b'), (5, 'a'), (6, 'b'), (7, 'b')]
+        );
+
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let mut n: LinkedList<i32> = (0..3).collect();
+        m.merge(&mut n);
+        check_links(&m);
+        assert_eq!(m.back(), Some(&2));
+        m.merge(&mut n);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut m: LinkedList<i32> = [1, 1, 2, 3, 3, 3, 1, 4, 4].into_iter().collect();
+        m.dedup();
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[1, 2, 3, 1, 4]);
+        assert_eq!(m.back(), Some(&4));
+
+        let mut m: LinkedList<i32> = [10, 11, 20, 21, 22, 30].into_iter().collect();
+        m.dedup_by_key(|x| *x / 10);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[10, 20, 30]);
+
+        let mut m: LinkedList<i32> = LinkedList::new();
+        m.dedup();
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_node_pool() {
+        let mut m: LinkedList<u64, NodePool> = LinkedList::with_capacity(4);
+        assert_eq!(m.capacity(), 4);
+        assert_eq!(m.allocator().spare(), 4);
+
+        m.extend([1, 2, 3]);
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.capacity(), 4);
+        assert_eq!(m.allocator().spare(), 1);
+
+        // pop出来的节点回到节点池，下一次push直接复用同一块内存
+        let addr = m.back().unwrap() as *const u64;
+        assert_eq!(m.pop_back(), Some(3));
+        assert_eq!(m.allocator().spare(), 2);
+        m.push_front(0);
+        assert_eq!(m.front().unwrap() as *const u64, addr);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 1, 2]);
+
+        m.reserve(10);
+        assert_eq!(m.capacity(), 13);
+        m.clear();
+        assert_eq!(m.capacity(), 13);
+        m.shrink_to_fit();
+        assert_eq!(m.capacity(), 0);
+
+        // 切割出来的链表有自己的节点池，原链表的空闲节点保留在原处
+        m.extend(0..6);
+        m.reserve(2);
+        let mut tail = m.split_off(3);
+        assert_eq!(m.allocator().spare(), 2);
+        assert_eq!(tail.allocator().spare(), 0);
+        tail.pop_front();
+        assert_eq!(tail.allocator().spare(), 1);
+        m.append(&mut tail);
+        assert_eq!(tail.allocator().spare(), 1);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 1, 2, 4, 5]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.insert_after(10);
+        assert_eq!(cursor.remove_current(), Some(0));
+        assert_eq!(m.allocator().spare(), 2);
+
+        let copy = m.clone();
+        assert_eq!(copy, m);
+        assert_eq!(copy.allocator().spare(), 0);
+    }
+
+    thread_local! {
+        static LIVE_NODES: Cell<isize> = const { Cell::new(0) };
+    }
+
+    // 统计还没释放的节点数量，用来检查节点有没有泄漏或者重复释放
+    #[derive(Default)]
+    struct CountingAlloc;
+
+    unsafe impl NodeAlloc for CountingAlloc {
+        fn allocate(&mut self, layout: Layout) -> NonNull<u8> {
+            LIVE_NODES.with(|n| n.set(n.get() + 1));
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&mut self, ptr: NonNull<u8>, layout: Layout) {
+            LIVE_NODES.with(|n| n.set(n.get() - 1));
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn test_custom_alloc() {
+        {
+            let mut m: LinkedList<String, CountingAlloc> = LinkedList::new_in(CountingAlloc);
+            m.extend((0..10).map(|i| i.to_string()));
+            assert_eq!(LIVE_NODES.with(Cell::get), 10);
+
+            let mut tail = m.split_off(4);
+            tail.retain(|s| s != "5");
+            m.append(&mut tail);
+            assert_eq!(LIVE_NODES.with(Cell::get), 9);
+
+            m.sort_by(|a, b| b.cmp(a));
+            let drained: Vec<_> = m.drain(..3).collect();
+            assert_eq!(drained, &["9", "8", "7"]);
+            assert_eq!(LIVE_NODES.with(Cell::get), 6);
+
+            let mut cursor = m.cursor_mut();
+            cursor.insert_before("x".to_string());
+            cursor.move_next();
+            let single = cursor.remove_current_as_list().unwrap();
+            assert_eq!(LIVE_NODES.with(Cell::get), 7);
+            drop(single);
+            assert_eq!(m.len(), 6);
+        }
+        assert_eq!(LIVE_NODES.with(Cell::get), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let m = generate_test();
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(json, "[0,1,2,3,4,5,6]");
+
+        let n: LinkedList<i32> = serde_json::from_str(&json).unwrap();
+        check_links(&n);
+        assert_eq!(n, m);
+
+        let p: LinkedList<i32, NodePool> = serde_json::from_str("[]").unwrap();
+        assert!(p.is_empty());
+        assert!(serde_json::from_str::<LinkedList<i32>>("[1, \"2\"]").is_err());
+    }
+
+    // 下面的miri_*测试专门用来喂给Miri，覆盖cursor、切割和拼接中的unsafe代码：
+    // cargo +nightly miri test linkedlist
+    #[test]
+    fn miri_food() {
+        let mut list = LinkedList::new();
+
+        list.push_back(1);
+        list.push_front(0);
+        list.push_back(2);
+        list.push_back(3);
+        *list.front_mut().unwrap() *= 10;
+        *list.back_mut().unwrap() *= 10;
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.pop_back(), Some(30));
+
+        for elem in list.iter_mut() {
+            *elem *= 100;
+        }
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&100));
+        assert_eq!(iter.next_back(), Some(&200));
+        assert_eq!(iter.next(), None);
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        // 可变引用一个接一个地使用，不会同时存在
+        *cursor.current().unwrap() += 1;
+        *cursor.peek_next().unwrap() += 2;
+        assert_eq!(cursor.peek_prev(), None);
+        cursor.move_next();
+        *cursor.peek_prev().unwrap() += 3;
+        assert_eq!(cursor.as_cursor().current(), Some(&202));
+        cursor.insert_after(300);
+        cursor.insert_before(150);
+        assert_eq!(cursor.remove_current(), Some(202));
+        assert_eq!(cursor.current(), Some(&mut 300));
+
+        let readers = (list.cursor_front(), list.cursor_back());
+        assert_eq!(readers.0.current(), Some(&104));
+        assert_eq!(readers.1.current(), Some(&300));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[104, 150, 300]);
+
+        // Drop it on the ground and let the dtor exercise itself
+    }
+
+    #[test]
+    fn miri_cursor_split_edges() {
+        let mut m: LinkedList<u32> = (0..4).collect();
+
+        // cursor在链表头，split_before返回空链表
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        let before = cursor.split_before();
+        assert!(before.is_empty());
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 0));
+        drop(before);
+
+        // cursor在链表尾，split_after返回空链表
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        let after = cursor.split_after();
+        assert!(after.is_empty());
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        drop(after);
+        check_links(&m);
+        assert_eq!(m.len(), 4);
+
+        // 从中间切开，index保持不变
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&after);
+        check_links(&before);
+        assert_eq!(after.into_iter().collect::<Vec<_>>(), &[2, 3]);
+        assert_eq!(before.into_iter().collect::<Vec<_>>(), &[0]);
+        check_links(&m);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[1]);
+    }
+
+    #[test]
+    fn miri_cursor_splice() {
+        let mut m: LinkedList<String> = LinkedList::new();
+        let mut cursor = m.cursor_mut();
+
+        // 在空链表上拼接，直接替换
+        cursor.splice_after(["b".to_string()].into_iter().collect());
+        cursor.splice_before(LinkedList::new());
+        cursor.move_next();
+        cursor.splice_before(["a".to_string()].into_iter().collect());
+        cursor.splice_after(["c".to_string(), "d".to_string()].into_iter().collect());
+        assert_eq!(cursor.index(), Some(1));
+
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.splice_before(["e".to_string()].into_iter().collect());
+        cursor.splice_after(["_".to_string()].into_iter().collect());
+        assert_eq!(cursor.index(), None);
+
+        let mut moved = cursor.remove_current_as_list();
+        assert!(moved.is_none());
+        cursor.move_next();
+        moved = cursor.remove_current_as_list();
+        cursor.move_prev();
+        cursor.splice_after(moved.unwrap());
+        check_links(&m);
+        assert_eq!(m.len(), 6);
+        assert_eq!(m.iter().map(|s| s.as_str()).collect::<String>(), "_abcde");
+    }
+
+    #[test]
+    fn miri_handles() {
+        let mut m = list_from(&[1, 2, 3]);
+        assert_eq!(LinkedList::<i32>::new().front_handle(), None);
+        let first = m.front_handle().unwrap();
+        let last = m.back_handle().unwrap();
+        assert_ne!(first, last);
+
+        unsafe {
+            assert_eq!(m.get_unchecked(first), &1);
+            *m.get_unchecked_mut(last) = 30;
+            let elem = first.as_ptr();
+
+            // 移到头部/尾部，已经在头部/尾部时什么都不做
+            m.move_to_front_unchecked(last);
+            assert_eq!(m.iter().copied().collect::<Vec<_>>(), [30, 1, 2]);
+            m.move_to_front_unchecked(last);
+            m.move_to_back_unchecked(first);
+            check_links(&m);
+            assert_eq!(m.iter().copied().collect::<Vec<_>>(), [30, 2, 1]);
+            assert_eq!(m.back_handle(), Some(first));
+            // 移动节点不会让as_ptr得到的指针失效
+            assert_eq!(*elem, 1);
+
+            assert_eq!(m.remove_unchecked(first), 1);
+            assert_eq!(m.remove_unchecked(last), 30);
+            check_links(&m);
+            assert_eq!(m.len(), 1);
+            let only = m.front_handle().unwrap();
+            m.move_to_back_unchecked(only);
+            m.move_to_front_unchecked(only);
+            assert_eq!(m.remove_unchecked(only), 2);
+        }
+        assert!(m.is_empty());
+        assert_eq!(m.back_handle(), None);
+        m.push_back(4);
+        assert_eq!(m.front(), Some(&4));
+    }
+
+    fn check_links<T: Eq + std::fmt::Debug, A: NodeAlloc>(list: &LinkedList<T, A>) {
         let from_front: Vec<_> = list.iter().collect();
         let from_back: Vec<_> = list.iter().rev().collect();
         let re_reved: Vec<_> = from_back.into_iter().rev().collect();
@@ -1,7 +1,11 @@
-use std::mem;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 
 pub struct List<T> {
     head: Link<T>,
+    // 记录节点个数，len()不需要遍历
+    len: usize,
 }
 
 // 类型别名,代码简洁的写法，更加美观
@@ -15,37 +19,62 @@ struct Node<T> {
 // 通过Iterator特征来实现迭代，IntoIter只是简单取走值，不涉及引用和生命周期
 pub struct IntoIter<T>(List<T>);
 
+// 迭代器也记录剩余的个数，用来实现ExactSizeIterator
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
+    len: usize,
 }
 
 pub struct IterMut<'a, T> {
     next: Option<&'a mut Node<T>>,
+    len: usize,
 }
 
 // 这里前边的 T 表示声明泛型类型，后边的 T 代表了具体的某一个类型。
 impl<T> List<T> {
     pub fn new() -> Self {
-        List { head: None }
+        List { head: None, len: 0 }
     }
     
     pub fn push(&mut self, elem: T) {
         let new_node = Box::new(Node {
-            elem: elem,
+            elem,
             // 代替 mem::replace
             next: self.head.take(),
         });
         self.head = Some(new_node);
+        self.len += 1;
     }
 
     // 用map 代替 match option { None => None, Some(x) => Some(y) }
     pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|node| {
             self.head = node.next;
+            self.len -= 1;
             node.elem
         })
     }
 
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // 原地反转，只修改每个节点的next，不移动也不重新分配节点
+    pub fn reverse(&mut self) {
+        let mut prev: Link<T> = None;
+        let mut cur = self.head.take();
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+            node.next = prev;
+            prev = Some(node);
+        }
+        self.head = prev;
+    }
+
     // 获取链头部元素
     pub fn peek(&self) -> Option<&T> {
         // 让 map 作用在引用上，而不是直接作用在 self.head 上
@@ -61,45 +90,161 @@ impl<T> List<T> {
         })
     }
 
-    // 迭代器
-
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+    // 删除并返回第一个满足条件的元素，其他节点保持原来的顺序
+    pub fn remove_first<F: FnMut(&T) -> bool>(&mut self, mut pred: F) -> Option<T> {
+        // cur始终指向要检查的那个Link，找到之后直接把它换成下一个节点
+        let mut cur = &mut self.head;
+        while cur.as_ref().is_some_and(|node| !pred(&node.elem)) {
+            cur = &mut cur.as_mut().unwrap().next;
+        }
+        let node = cur.take()?;
+        *cur = node.next;
+        self.len -= 1;
+        Some(node.elem)
     }
 
-    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+    // 迭代器，into_iter由下面的IntoIterator实现提供
+
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
+            len: self.len,
         }
     }
 
-    pub fn iter_mut<'a>(&'a mut self) -> IterMut<'a, T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             next: self.head.as_deref_mut(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 逐个push，和push的语义一致：最后一个元素在栈顶
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
         }
     }
 }
 
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// 从头到尾复制，保持原来的顺序
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        let mut list = Self::new();
+        // tail始终指向新链表最后一个Link，直接在尾部追加
+        let mut tail = &mut list.head;
+        for elem in self.iter() {
+            let node = tail.insert(Box::new(Node {
+                elem: elem.clone(),
+                next: None,
+            }));
+            tail = &mut node.next;
+        }
+        list.len = self.len;
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+// 先写入长度，避免不同的链表拼接后得到相同的哈希输入
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
 impl<T> Iterator for IntoIter<T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         // 直接转移了所有权，所以不涉及到生命周期
         self.0.pop()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
 }
 
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
     
     fn next(&mut self) -> Option<Self::Item> {
         self.next.map(|node| {
             self.next = node.next.as_deref();
+            self.len -= 1;
             // 因为返回的是node.elem的引用，所以需要标注生命周期
             &node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
     // Option 和不可变引用 &T 恰恰是可以 Copy 的
@@ -107,17 +252,40 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.next.take().map(|node| {
             self.next = node.next.as_deref_mut();
+            self.len -= 1;
             &mut node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+// 序列化为从链表头（栈顶）开始的序列
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
 }
 
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        // push总是插在头部，所以要从最后一个元素开始push才能保持原来的顺序
+        Ok(elems.into_iter().rev().collect())
+    }
+}
 
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
-        let mut cur_link = mem::replace(&mut self.head, None);
+        let mut cur_link = self.head.take();
         while let Some(mut boxed_node) = cur_link {
-            cur_link = mem::replace(&mut boxed_node.next, None);
+            cur_link = boxed_node.next.take();
         }
     }
 }
@@ -125,6 +293,8 @@ impl<T> Drop for List<T> {
 #[cfg(test)]
 mod test {
     use super::List;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
 
     #[test]
     fn basics() {
@@ -214,6 +384,23 @@ mod test {
         assert_eq!(iter.next(), Some(&1));
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let mut list = List::new();
+        list.push(1); list.push(2); list.push(3);
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, "[3,2,1]");
+
+        let list: List<i32> = serde_json::from_str(&json).unwrap();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), None);
+    }
+
     #[test]
     fn iter_mut() {
         let mut list = List::new();
@@ -224,4 +411,110 @@ mod test {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
+
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+
+        list.extend([1, 2, 3]);
+        assert_eq!(list.len(), 3);
+        list.pop();
+        assert_eq!(list.len(), 2);
+        list.pop();
+        list.pop();
+        list.pop();
+        assert_eq!(list.len(), 0);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list: List<i32> = (1..=5).collect();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [5, 4, 3, 2, 1]);
+        list.reverse();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.peek(), Some(&1));
+
+        let mut empty: List<i32> = List::new();
+        empty.reverse();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn remove_first() {
+        let mut list: List<i32> = (1..=5).collect();
+        // 链头、中间、链尾
+        assert_eq!(list.remove_first(|&x| x == 5), Some(5));
+        assert_eq!(list.remove_first(|&x| x % 2 == 0), Some(4));
+        assert_eq!(list.remove_first(|&x| x == 1), Some(1));
+        assert_eq!(list.remove_first(|&x| x == 42), None);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 2]);
+        assert_eq!(list.len(), 2);
+
+        assert_eq!(list.remove_first(|_| true), Some(3));
+        assert_eq!(list.remove_first(|_| true), Some(2));
+        assert_eq!(list.remove_first(|_| true), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn exact_size() {
+        let mut list: List<i32> = (0..4).collect();
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 4);
+        iter.next();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+
+        let mut iter_mut = list.iter_mut();
+        iter_mut.next();
+        iter_mut.next();
+        assert_eq!(iter_mut.len(), 2);
+
+        let mut into_iter = list.into_iter();
+        into_iter.next();
+        assert_eq!(into_iter.len(), 3);
+        assert_eq!(into_iter.by_ref().count(), 3);
+        assert_eq!(into_iter.len(), 0);
+    }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn traits() {
+        let mut list: List<String> = List::default();
+        list.extend(["a", "b", "c"].iter().map(|s| s.to_string()));
+
+        let copy = list.clone();
+        assert_eq!(copy, list);
+        assert_eq!(copy.len(), 3);
+        assert_eq!(hash_of(&copy), hash_of(&list));
+        assert_eq!(format!("{:?}", copy), r#"["c", "b", "a"]"#);
+
+        for elem in &mut list {
+            elem.make_ascii_uppercase();
+        }
+        assert_ne!(copy, list);
+        assert_eq!((&list).into_iter().cloned().collect::<Vec<_>>(), ["C", "B", "A"]);
+
+        // 按引用遍历
+        let mut total = 0;
+        for elem in &copy {
+            total += elem.len();
+        }
+        assert_eq!(total, 3);
+
+        // 前缀相同但长度不同
+        let mut shorter = copy.clone();
+        shorter.pop();
+        assert_ne!(shorter, copy);
+        assert_ne!(hash_of(&shorter), hash_of(&copy));
+    }
 }
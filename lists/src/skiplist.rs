@@ -0,0 +1,565 @@
+use std::borrow::Borrow;
+use std::fmt::{self, Debug};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::{self, NonNull};
+
+// 最多的层数，按每升一层1/2的概率，足够容纳2^32个元素
+const MAX_LEVEL: usize = 32;
+
+// new()使用的固定种子，保证不指定种子时每次运行的结构也完全相同
+const DEFAULT_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// 跳表：level 0 是一条按key排序的双向链表，上面每一层都是下一层的稀疏子集，
+// 查找时从最高层开始往右走，走不动了就下降一层，平均O(log n)
+pub struct SkipList<K, V> {
+    // 每一层的第一个节点
+    head: [Link<K, V>; MAX_LEVEL],
+    // level 0 的最后一个节点
+    tail: Link<K, V>,
+    // 当前实际使用的层数
+    level: usize,
+    len: usize,
+    // 生成节点层数的xorshift状态
+    rng: u64,
+    _boo: PhantomData<(K, V)>,
+}
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // level 0 的前一个节点，用来反向遍历
+    prev: Link<K, V>,
+    // 每一层的下一个节点，长度就是节点的层数
+    next: Box<[Link<K, V>]>,
+}
+
+// 按key顺序遍历[front, back]之间的节点，两端相遇后结束
+pub struct Range<'a, K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    _boo: PhantomData<&'a (K, V)>,
+}
+
+impl<K, V> SkipList<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    // 相同的种子和相同的插入顺序得到完全相同的结构，方便复现问题
+    pub fn with_seed(seed: u64) -> Self {
+        SkipList {
+            head: [None; MAX_LEVEL],
+            tail: None,
+            level: 0,
+            len: 0,
+            // xorshift的状态不能为0
+            rng: if seed == 0 { DEFAULT_SEED } else { seed },
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        let mut cur = self.head[0];
+        while let Some(node) = cur {
+            unsafe {
+                let node = Box::from_raw(node.as_ptr());
+                cur = node.next[0];
+            }
+        }
+        self.head = [None; MAX_LEVEL];
+        self.tail = None;
+        self.level = 0;
+        self.len = 0;
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        unsafe { self.head[0].map(|node| Self::entry(node)) }
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        unsafe { self.tail.map(|node| Self::entry(node)) }
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range {
+            front: self.head[0],
+            back: self.tail,
+            _boo: PhantomData,
+        }
+    }
+
+    unsafe fn entry<'a>(node: NonNull<Node<K, V>>) -> (&'a K, &'a V) {
+        (&(*node.as_ptr()).key, &(*node.as_ptr()).value)
+    }
+
+    // 节点层数：每多一层的概率是1/2
+    fn random_level(&mut self) -> usize {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng = x;
+        (x.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    // node在level层的下一个节点，node为None表示从head开始
+    unsafe fn next(&self, node: Link<K, V>, level: usize) -> Link<K, V> {
+        match node {
+            Some(node) => (*node.as_ptr()).next[level],
+            None => self.head[level],
+        }
+    }
+
+    // 从最高层开始往右走，只要下一个节点的key满足before就前进，走不动了就下降一层
+    // 返回level 0 上最后一个满足before的节点，None表示没有（也就是head）
+    unsafe fn last_before<F: FnMut(&K) -> bool>(&self, mut before: F) -> Link<K, V> {
+        let mut cur = None;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.next(cur, level) {
+                if !before(&(*next.as_ptr()).key) {
+                    break;
+                }
+                cur = Some(next);
+            }
+        }
+        cur
+    }
+
+    // 和last_before一样查找，同时记录每一层停下来的位置，也就是插入或删除时需要修改的指针
+    unsafe fn find_slots<F: FnMut(&K) -> bool>(&mut self, mut before: F) -> (Link<K, V>, [*mut Link<K, V>; MAX_LEVEL]) {
+        let mut slots = [ptr::null_mut(); MAX_LEVEL];
+        let mut cur: Link<K, V> = None;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.next(cur, level) {
+                if !before(&(*next.as_ptr()).key) {
+                    break;
+                }
+                cur = Some(next);
+            }
+            slots[level] = match cur {
+                Some(node) => &mut (*node.as_ptr()).next[level],
+                None => &mut self.head[level],
+            };
+        }
+        (cur, slots)
+    }
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    // 插入键值对，key已经存在时替换value并返回旧值
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        unsafe {
+            let (prev, mut slots) = self.find_slots(|k| *k < key);
+            if let Some(node) = self.next(prev, 0) {
+                if (*node.as_ptr()).key == key {
+                    return Some(mem::replace(&mut (*node.as_ptr()).value, value));
+                }
+            }
+
+            let height = self.random_level();
+            // 新节点比当前所有节点都高，多出来的层直接从head开始
+            for (level, slot) in slots.iter_mut().enumerate().take(height).skip(self.level) {
+                *slot = &mut self.head[level];
+            }
+            self.level = self.level.max(height);
+
+            let node = NonNull::from(Box::leak(Box::new(Node {
+                key,
+                value,
+                prev,
+                next: vec![None; height].into_boxed_slice(),
+            })));
+            for (level, slot) in slots.iter().enumerate().take(height) {
+                (*node.as_ptr()).next[level] = **slot;
+                **slot = Some(node);
+            }
+            match (*node.as_ptr()).next[0] {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+            self.len += 1;
+            None
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe { self.find(key).map(|node| &(*node.as_ptr()).value) }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe { self.find(key).map(|node| &mut (*node.as_ptr()).value) }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    // 删除key并返回对应的value
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe {
+            let (prev, slots) = self.find_slots(|k| k.borrow() < key);
+            let node = self.next(prev, 0)?;
+            if (*node.as_ptr()).key.borrow() != key {
+                return None;
+            }
+
+            // 在节点存在的每一层，停下来的位置指向的正是这个节点
+            let next = &(*node.as_ptr()).next;
+            for (level, slot) in slots.iter().enumerate().take(next.len()) {
+                **slot = next[level];
+            }
+            match next[0] {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.tail = prev,
+            }
+            while self.level > 0 && self.head[self.level - 1].is_none() {
+                self.level -= 1;
+            }
+            self.len -= 1;
+            Some(Box::from_raw(node.as_ptr()).value)
+        }
+    }
+
+    // 按key的范围遍历，start大于end时返回空的迭代器
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        unsafe {
+            let front = match range.start_bound() {
+                Bound::Included(start) => self.next(self.last_before(|k| k.borrow() < start), 0),
+                Bound::Excluded(start) => self.next(self.last_before(|k| k.borrow() <= start), 0),
+                Bound::Unbounded => self.head[0],
+            };
+            let back = match range.end_bound() {
+                Bound::Included(end) => self.last_before(|k| k.borrow() <= end),
+                Bound::Excluded(end) => self.last_before(|k| k.borrow() < end),
+                Bound::Unbounded => self.tail,
+            };
+            match (front, back) {
+                (Some(f), Some(b)) if (*f.as_ptr()).key <= (*b.as_ptr()).key => Range {
+                    front,
+                    back,
+                    _boo: PhantomData,
+                },
+                _ => Range {
+                    front: None,
+                    back: None,
+                    _boo: PhantomData,
+                },
+            }
+        }
+    }
+
+    fn find<Q>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe {
+            let node = self.next(self.last_before(|k| k.borrow() < key), 0)?;
+            if (*node.as_ptr()).key.borrow() == key {
+                Some(node)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+impl<K, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipList<K, V> {
+    type IntoIter = Range<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = unsafe { (*node.as_ptr()).next[0] };
+        }
+        unsafe { Some(SkipList::entry(node)) }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = unsafe { (*node.as_ptr()).prev };
+        }
+        unsafe { Some(SkipList::entry(node)) }
+    }
+}
+
+impl<K, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+unsafe impl<K: Send, V: Send> Send for SkipList<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipList<K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Range<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Range<'a, K, V> {}
+
+#[cfg(test)]
+mod test {
+    use super::SkipList;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    // 检查每一层都是有序的，并且上一层是下一层的子集
+    fn check_structure<K: Ord, V>(list: &SkipList<K, V>) -> Vec<usize> {
+        let mut heights = Vec::new();
+        unsafe {
+            let mut prev = None;
+            let mut cur = list.head[0];
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).prev, prev);
+                let next = (*node.as_ptr()).next[0];
+                if let Some(next) = next {
+                    assert!((*node.as_ptr()).key < (*next.as_ptr()).key);
+                }
+                let tower: &[_] = &(*node.as_ptr()).next;
+                heights.push(tower.len());
+                prev = cur;
+                cur = next;
+            }
+            assert_eq!(list.tail, prev);
+
+            for level in 1..list.level {
+                let mut count = 0;
+                let mut cur = list.head[level];
+                while let Some(node) = cur {
+                    let tower: &[_] = &(*node.as_ptr()).next;
+                    assert!(tower.len() > level);
+                    count += 1;
+                    cur = (*node.as_ptr()).next[level];
+                }
+                assert_eq!(count, heights.iter().filter(|&&h| h > level).count());
+            }
+        }
+        assert_eq!(heights.len(), list.len());
+        assert_eq!(list.level, heights.iter().copied().max().unwrap_or(0));
+        heights
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = SkipList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.first(), None);
+        assert_eq!(list.last(), None);
+        assert_eq!(list.remove(&1), None);
+
+        for i in [5, 1, 9, 3, 7] {
+            assert_eq!(list.insert(i, i * 10), None);
+        }
+        check_structure(&list);
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.get(&3), Some(&30));
+        assert_eq!(list.get(&4), None);
+        assert!(list.contains_key(&9));
+        assert_eq!(list.first(), Some((&1, &10)));
+        assert_eq!(list.last(), Some((&9, &90)));
+
+        // 已存在的key替换value
+        assert_eq!(list.insert(3, 33), Some(30));
+        *list.get_mut(&5).unwrap() += 5;
+        assert_eq!(list.len(), 5);
+
+        assert_eq!(list.remove(&9), Some(90));
+        assert_eq!(list.remove(&1), Some(10));
+        assert_eq!(list.remove(&1), None);
+        check_structure(&list);
+        assert_eq!(list.first(), Some((&3, &33)));
+        assert_eq!(list.last(), Some((&7, &70)));
+        assert_eq!(format!("{:?}", list), "{3: 33, 5: 55, 7: 70}");
+
+        list.clear();
+        assert!(list.is_empty());
+        list.insert(1, 1);
+        assert_eq!(list.iter().count(), 1);
+    }
+
+    #[test]
+    fn range() {
+        let list: SkipList<i32, ()> = (0..100).map(|i| (i * 2, ())).collect();
+        let keys = |r: super::Range<'_, i32, ()>| r.map(|(k, _)| *k).collect::<Vec<_>>();
+
+        assert_eq!(keys(list.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(list.range(11..=16)), [12, 14, 16]);
+        assert_eq!(keys(list.range((Bound::Excluded(10), Bound::Excluded(16)))), [12, 14]);
+        assert_eq!(keys(list.range(..4)), [0, 2]);
+        assert_eq!(keys(list.range(195..)), [196, 198]);
+        assert_eq!(keys(list.range(11..12)), Vec::<i32>::new());
+        assert_eq!(keys(list.range(300..)), Vec::<i32>::new());
+        assert_eq!(keys(list.range((Bound::Included(50), Bound::Excluded(10)))), Vec::<i32>::new());
+        assert_eq!(list.range(..).count(), 100);
+
+        // 两端交替遍历，相遇后结束
+        let mut range = list.range(10..=20);
+        assert_eq!(range.next(), Some((&10, &())));
+        assert_eq!(range.next_back(), Some((&20, &())));
+        assert_eq!(range.rev().map(|(k, _)| *k).collect::<Vec<_>>(), [18, 16, 14, 12]);
+
+        // 用Borrow查找
+        let strings: SkipList<String, usize> = ["b", "a", "c"].iter().map(|s| (s.to_string(), s.len())).collect();
+        assert_eq!(strings.get("a"), Some(&1));
+        assert_eq!(strings.range::<str, _>((Bound::Included("b"), Bound::Unbounded)).count(), 2);
+    }
+
+    #[test]
+    fn deterministic_seed() {
+        let build = |seed| {
+            let mut list = SkipList::with_seed(seed);
+            for i in 0..1000 {
+                list.insert((i * 7919) % 1000, ());
+            }
+            check_structure(&list)
+        };
+        assert_eq!(build(42), build(42));
+        assert_ne!(build(42), build(43));
+
+        // 层数大致按1/2递减
+        let heights = build(1);
+        let tall = heights.iter().filter(|&&h| h >= 4).count();
+        assert!(tall > 50 && tall < 250, "{} nodes of height >= 4", tall);
+    }
+
+    #[test]
+    fn drop_values() {
+        use std::rc::Rc;
+
+        let counter = Rc::new(());
+        let mut list = SkipList::new();
+        for i in 0..100 {
+            list.insert(i, counter.clone());
+        }
+        list.insert(0, counter.clone());
+        assert_eq!(Rc::strong_count(&counter), 101);
+        list.remove(&50);
+        assert_eq!(Rc::strong_count(&counter), 100);
+        drop(list);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(u8, u32),
+        Remove(u8),
+        Get(u8),
+        Range(u8, u8, bool),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            3 => (any::<u8>(), any::<u32>()).prop_map(|(k, v)| Op::Insert(k, v)),
+            2 => any::<u8>().prop_map(Op::Remove),
+            1 => any::<u8>().prop_map(Op::Get),
+            1 => (any::<u8>(), any::<u8>(), any::<bool>()).prop_map(|(a, b, inclusive)| Op::Range(a.min(b), a.max(b), inclusive)),
+        ]
+    }
+
+    proptest! {
+        // 随机的操作序列，每一步的结果都和BTreeMap相同
+        #[test]
+        fn matches_btreemap(seed in any::<u64>(), ops in proptest::collection::vec(op(), 0..300)) {
+            let mut list = SkipList::with_seed(seed);
+            let mut model = BTreeMap::new();
+            for op in ops {
+                match op {
+                    Op::Insert(k, v) => prop_assert_eq!(list.insert(k, v), model.insert(k, v)),
+                    Op::Remove(k) => prop_assert_eq!(list.remove(&k), model.remove(&k)),
+                    Op::Get(k) => prop_assert_eq!(list.get(&k), model.get(&k)),
+                    Op::Range(lo, hi, true) => {
+                        prop_assert!(list.range(lo..=hi).eq(model.range(lo..=hi)));
+                        prop_assert!(list.range(lo..=hi).rev().eq(model.range(lo..=hi).rev()));
+                    }
+                    Op::Range(lo, hi, false) => {
+                        prop_assert!(list.range(lo..hi).eq(model.range(lo..hi)));
+                    }
+                }
+                prop_assert_eq!(list.len(), model.len());
+            }
+            check_structure(&list);
+            prop_assert!(list.iter().eq(model.iter()));
+            prop_assert_eq!(list.first(), model.iter().next());
+            prop_assert_eq!(list.last(), model.iter().next_back());
+        }
+    }
+}
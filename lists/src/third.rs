@@ -1,5 +1,11 @@
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
 use std::rc::Rc;
 
+// 用Arc实现的线程安全版本
+pub mod sync;
+
 // 当一个node多个链表共享的时候，就需要用Rc和Arc，来解决所有权问题
 pub struct List<T> {
     head: Link<T>,
@@ -7,6 +13,9 @@ pub struct List<T> {
 
 pub struct IntoIter<T>(List<T>);
 
+// 遇到共享节点时不复制，而是把剩下的链表交还给调用者
+pub struct TryIntoIter<T>(List<T>);
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -48,10 +57,62 @@ impl<T> List<T> {
         self.head.as_ref().map(|node| &node.elem)
     }
 
+    // 只有T: Clone时IntoIter才能迭代：和其他链表共享的节点不能拿走所有权，只能复制
     pub fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
 
+    // 依次拿走独占节点中的元素，遇到第一个共享节点时返回Err，里面是从这个节点开始的剩余链表
+    pub fn try_into_iter(self) -> TryIntoIter<T> {
+        TryIntoIter(self)
+    }
+
+    // 下面的操作都返回新链表，原链表不变；能共享的节点直接共享，不能共享的才复制
+
+    // 遍历一遍，O(n)
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // 两个链表是否指向同一个头节点，也就是完全共享
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    // 跳过前n个节点，只移动指针，不复制
+    pub fn drop(&self, n: usize) -> List<T> {
+        let mut link = &self.head;
+        for _ in 0..n {
+            match link {
+                Some(node) => link = &node.next,
+                None => break,
+            }
+        }
+        List { head: link.clone() }
+    }
+
+    // 用f映射每个元素，结果和原链表没有共享的节点
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> List<U> {
+        List::build(self.iter().map(f).collect(), None)
+    }
+
+    // 把elems按顺序接在tail前面，elems的第一个元素成为新的头
+    fn build(elems: Vec<T>, tail: Link<T>) -> List<T> {
+        let head = elems
+            .into_iter()
+            .rev()
+            .fold(tail, |next, elem| Some(Rc::new(Node { elem, next })));
+        List { head }
+    }
+
     pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
@@ -67,17 +128,128 @@ impl<T> List<T> {
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T: Clone> List<T> {
+    // 只复制self的节点，最后一个节点指向other，other整个被共享
+    pub fn append(&self, other: &List<T>) -> List<T> {
+        List::build(self.iter().cloned().collect(), other.head.clone())
+    }
+
+    pub fn reverse(&self) -> List<T> {
+        let head = self
+            .iter()
+            .fold(None, |next, elem| Some(Rc::new(Node { elem: elem.clone(), next })));
+        List { head }
+    }
+
+    // 最后一段全部保留的节点直接共享，只复制它前面被保留的元素
+    pub fn filter<P: FnMut(&T) -> bool>(&self, mut pred: P) -> List<T> {
+        let mut kept = Vec::new();
+        // suffix开始的节点目前都被保留了，suffix_at是suffix之前保留的元素个数
+        let mut suffix = &self.head;
+        let mut suffix_at = 0;
+        let mut link = &self.head;
+        while let Some(node) = link {
+            if pred(&node.elem) {
+                kept.push(&node.elem);
+            } else {
+                suffix = &node.next;
+                suffix_at = kept.len();
+            }
+            link = &node.next;
+        }
+        kept.truncate(suffix_at);
+        List::build(kept.into_iter().cloned().collect(), suffix.clone())
+    }
+
+    // 前n个元素，n不小于长度时直接共享整个链表
+    pub fn take(&self, n: usize) -> List<T> {
+        if self.drop(n).is_empty() {
+            return self.clone();
+        }
+        List::build(self.iter().take(n).cloned().collect(), None)
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 只增加头节点的引用计数
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
+// 第一个元素成为链表的头，和iter的顺序一致
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        List::build(iter.into_iter().collect(), None)
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// 和second::List一样逐个比较。T只要求PartialEq，元素可能不等于自身（比如NaN），
+// 所以走到共享的节点时也不能直接认为后面相同
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+// 和second::List一样先写入长度，避免不同的链表拼接后得到相同的哈希输入
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T: Clone> Iterator for IntoIter<T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.head.take().and_then(|rc_node| {
+        self.0.head.take().map(|rc_node| {
             // Rc::try_unwrap 判断是否只有一个强引用
-            if let Ok(mut node) = Rc::try_unwrap(rc_node) {
+            match Rc::try_unwrap(rc_node) {
+                Ok(mut node) => {
+                    self.0.head = node.next.take();
+                    node.elem
+                }
+                // 节点还被其他链表引用，复制元素，只增加下一个节点的引用计数
+                Err(rc_node) => {
+                    self.0.head = rc_node.next.clone();
+                    rc_node.elem.clone()
+                }
+            }
+        })
+    }
+}
+
+impl<T> Iterator for TryIntoIter<T> {
+    type Item = Result<T, List<T>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.head.take().map(|rc_node| match Rc::try_unwrap(rc_node) {
+            Ok(mut node) => {
                 self.0.head = node.next.take();
-                Some(node.elem)
-            } else {
-                None
+                Ok(node.elem)
             }
+            // head已经被取走，下一次next返回None
+            Err(rc_node) => Err(List {
+                head: Some(rc_node),
+            }),
         })
     }
 }
@@ -104,6 +276,22 @@ impl <'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
+// 每个链表值都序列化为从自己的head开始的序列，和其他链表共享的尾部也会完整地写出来
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        Ok(List::build(elems, None))
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         let mut head = self.head.take();
@@ -122,6 +310,8 @@ impl<T> Drop for List<T> {
 #[cfg(test)]
 mod test {
     use super::List;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
 
     #[test]
     fn basics() {
@@ -159,6 +349,43 @@ mod test {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn into_iter_shared_tail() {
+        let base = List::new().prepend(1).prepend(2);
+        let a = base.prepend(3);
+        let b = base.prepend(4).prepend(5);
+        drop(base);
+
+        // a独占节点3，节点2和1和b共享，这些元素复制出来，b不受影响
+        assert_eq!(a.into_iter().collect::<Vec<_>>(), [3, 2, 1]);
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), [5, 4, 2, 1]);
+
+        // a被消费后，b独占了整个链表
+        assert_eq!(b.into_iter().collect::<Vec<_>>(), [5, 4, 2, 1]);
+    }
+
+    #[test]
+    fn try_into_iter() {
+        let base = List::new().prepend(String::from("x")).prepend(String::from("y"));
+        let a = base.prepend(String::from("a1")).prepend(String::from("a2"));
+
+        let mut iter = a.try_into_iter();
+        assert_eq!(iter.next().and_then(Result::ok).as_deref(), Some("a2"));
+        assert_eq!(iter.next().and_then(Result::ok).as_deref(), Some("a1"));
+        // 第一个共享节点
+        let rest = match iter.next() {
+            Some(Err(rest)) => rest,
+            _ => panic!("expected the shared tail"),
+        };
+        assert_eq!(rest.head().map(String::as_str), Some("y"));
+        assert!(iter.next().is_none());
+
+        // 交还的尾部和base是同一段节点，drop掉base后可以完整地拿走
+        drop(base);
+        let elems: Result<Vec<_>, _> = rest.try_into_iter().collect();
+        assert_eq!(elems.ok().unwrap(), ["y", "x"]);
+    }
+
     #[test]
     fn iter() {
         let list = List::new().prepend(1).prepend(2).prepend(3);
@@ -169,6 +396,23 @@ mod test {
         assert_eq!(iter.next(), Some(&1));
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let base = List::new().prepend(1).prepend(2);
+        let a = base.prepend(3);
+        let b = base.prepend(4).prepend(5);
+
+        // 共享尾部的两个链表各自保持自己的顺序
+        assert_eq!(serde_json::to_string(&base).unwrap(), "[2,1]");
+        assert_eq!(serde_json::to_string(&a).unwrap(), "[3,2,1]");
+        assert_eq!(serde_json::to_string(&b).unwrap(), "[5,4,2,1]");
+
+        let list: List<i32> = serde_json::from_str("[5,4,2,1]").unwrap();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[5, 4, 2, 1]);
+        assert_eq!(list.head(), Some(&5));
+    }
+
    // 测试
    #[test]
    fn iter_mut() {
@@ -181,5 +425,103 @@ mod test {
        assert_eq!(iter.next(), Some(&mut 2));
        assert_eq!(iter.next(), None);
    }
-}
 
+    fn to_vec(list: &List<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn append() {
+        let a: List<i32> = (1..=3).collect();
+        let b: List<i32> = (4..=5).collect();
+        assert_eq!(a.len(), 3);
+
+        let ab = a.append(&b);
+        assert_eq!(to_vec(&ab), [1, 2, 3, 4, 5]);
+        // b整个被共享，a的节点被复制
+        assert!(ab.drop(3).ptr_eq(&b));
+        assert!(!ab.ptr_eq(&a));
+        assert_eq!(to_vec(&a), [1, 2, 3]);
+
+        assert!(List::new().append(&b).ptr_eq(&b));
+        assert_eq!(to_vec(&a.append(&List::new())), [1, 2, 3]);
+    }
+
+    #[test]
+    fn reverse_map() {
+        let list: List<i32> = (1..=4).collect();
+        assert_eq!(to_vec(&list.reverse()), [4, 3, 2, 1]);
+        assert!(List::<i32>::new().reverse().is_empty());
+
+        let strings = list.map(|x| x.to_string());
+        assert_eq!(strings.iter().cloned().collect::<String>(), "1234");
+    }
+
+    #[test]
+    fn filter() {
+        let list: List<i32> = [1, 2, 3, 4, 6, 8].iter().copied().collect();
+        let even = list.filter(|x| x % 2 == 0);
+        assert_eq!(to_vec(&even), [2, 4, 6, 8]);
+        // 4, 6, 8这一段全部保留，直接共享
+        assert!(even.drop(1).ptr_eq(&list.drop(3)));
+
+        // 全部保留时共享整个链表
+        assert!(list.filter(|_| true).ptr_eq(&list));
+        assert!(list.filter(|_| false).is_empty());
+    }
+
+    #[test]
+    fn take_drop() {
+        let list: List<i32> = (1..=5).collect();
+        assert_eq!(to_vec(&list.take(2)), [1, 2]);
+        assert!(list.take(5).ptr_eq(&list));
+        assert!(list.take(10).ptr_eq(&list));
+        assert!(list.take(0).is_empty());
+
+        assert_eq!(to_vec(&list.drop(2)), [3, 4, 5]);
+        assert!(list.drop(2).ptr_eq(&list.tail().tail()));
+        assert!(list.drop(0).ptr_eq(&list));
+        assert!(list.drop(7).is_empty());
+    }
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equality() {
+        let tail: List<i32> = (10..1000).collect();
+        let a = tail.prepend(1).prepend(2);
+        let b = tail.prepend(1).prepend(2);
+        let c = tail.prepend(3).prepend(2);
+
+        // 走到共享的tail就结束比较
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(!a.ptr_eq(&b));
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        // 结构不同但元素相同
+        let copy: List<i32> = a.iter().copied().collect();
+        assert_eq!(a, copy);
+        assert_eq!(hash_of(&a), hash_of(&copy));
+        assert_ne!(a, copy.tail());
+        assert_ne!(hash_of(&a.take(2)), hash_of(&a.take(3)));
+
+        assert_eq!(List::<i32>::new(), List::default());
+        assert_eq!(format!("{:?}", a.take(3)), "[2, 1, 10]");
+    }
+
+    // 元素只实现PartialEq时，共享尾部里的NaN也要逐个比较
+    #[test]
+    fn partial_eq_shared_nan() {
+        let tail = List::new().prepend(f64::NAN);
+        let a = tail.prepend(1.0);
+        let b = tail.prepend(1.0);
+        assert_ne!(a, b);
+        assert_ne!(tail, tail.clone());
+        assert_eq!(a.tail().tail(), List::new());
+    }
+}
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+// 和third::List一样的持久化链表，只是用Arc代替Rc，引用计数是原子操作，
+// 所以链表（以及它共享的尾部）可以在线程之间传递和共享
+pub struct List<T> {
+    head: Link<T>,
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: Link<T>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None }
+    }
+
+    pub fn prepend(&self, elem: T) -> List<T> {
+        List {
+            head: Some(Arc::new(Node {
+                elem,
+                // 只增加引用计数，不复制数据
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    pub fn tail(&self) -> List<T> {
+        List {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let elems = Vec::<T>::deserialize(deserializer)?;
+        let mut list = List::new();
+        for elem in elems.into_iter().rev() {
+            list = list.prepend(elem);
+        }
+        Ok(list)
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            // 节点还被其他链表（可能在其他线程）引用，就停下来，剩下的交给最后一个持有者
+            // 不能用try_unwrap：两个线程可能同时看到引用计数为2而都失败，
+            // 之后较晚drop失败结果的那个线程就会递归释放整条尾部。into_inner保证恰好一个线程拿到节点
+            match Arc::into_inner(node) {
+                Some(mut node) => head = node.next.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn basics() {
+        let list = List::new();
+        assert_eq!(list.head(), None);
+
+        let list = list.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.head(), Some(&3));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&2));
+
+        let list = list.tail().tail();
+        assert_eq!(list.head(), None);
+        assert_eq!(list.tail().head(), None);
+    }
+
+    #[test]
+    fn send_between_threads() {
+        let base = List::new().prepend(1).prepend(2);
+
+        // 每个线程在共享的尾部前面加上自己的元素，再把新链表送回来
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let list = base.prepend(10 + i);
+                thread::spawn(move || list.prepend(20 + i))
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let list = handle.join().unwrap();
+            let expected = [20 + i as i32, 10 + i as i32, 2, 1];
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(base.iter().copied().collect::<Vec<_>>(), [2, 1]);
+    }
+
+    #[test]
+    fn share_between_threads() {
+        let list = Arc::new((0..1000).fold(List::new(), |list, i| list.prepend(i)));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let list = list.clone();
+                thread::spawn(move || list.iter().sum::<i32>())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), (0..1000).sum::<i32>());
+        }
+    }
+
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    // 共享尾部的链表在不同线程被drop，每个节点只会被释放一次，并且是在最后一个持有者drop的时候
+    #[test]
+    fn drop_across_threads() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut shared = List::new();
+        for _ in 0..100 {
+            shared = shared.prepend(DropCounter(drops.clone()));
+        }
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let list = shared.prepend(DropCounter(drops.clone()));
+                thread::spawn(move || drop(list))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        // 每个线程只释放了自己的头节点
+        assert_eq!(drops.load(Ordering::SeqCst), 4);
+
+        drop(shared);
+        assert_eq!(drops.load(Ordering::SeqCst), 104);
+    }
+
+    // 多个线程同时drop共享同一条长尾部的链表，不管哪个线程最后释放尾部，都不能递归drop导致栈溢出
+    #[test]
+    fn concurrent_drop_shared_tail() {
+        const THREADS: usize = 8;
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut shared = List::new();
+        for _ in 0..100000 {
+            shared = shared.prepend(DropCounter(drops.clone()));
+        }
+
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let lists: Vec<_> = (0..THREADS)
+            .map(|_| shared.prepend(DropCounter(drops.clone())))
+            .collect();
+        // 尾部只剩下这些链表持有
+        drop(shared);
+
+        let handles: Vec<_> = lists
+            .into_iter()
+            .map(|list| {
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    drop(list);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(drops.load(Ordering::SeqCst), 100000 + THREADS);
+    }
+
+    #[test]
+    fn long_list() {
+        let mut list = List::new();
+        for i in 0..100000 {
+            list = list.prepend(i);
+        }
+        let other = thread::spawn(move || drop(list));
+        other.join().unwrap();
+    }
+}
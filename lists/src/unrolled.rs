@@ -0,0 +1,1174 @@
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr::{self, NonNull};
+
+use crate::linkedlist::{unlink, LinkOps};
+
+// 每个节点最多存放的元素个数
+const NODE_CAP: usize = 16;
+
+// 展开链表：和linkedlist::LinkedList一样是双向链表，但每个节点存放一小段连续的元素，
+// 遍历时大部分访问都落在同一个节点里，指针跳转和cache miss都少得多
+pub struct UnrolledList<T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<T>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+// 元素连续存放在elems[..len]中，链表中不会出现空节点
+struct Node<T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    elems: [MaybeUninit<T>; NODE_CAP],
+}
+
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    // front节点中下一个要返回的下标
+    front_offset: usize,
+    back: Link<T>,
+    // back节点中已经返回过的第一个下标，下一次返回它前面的元素
+    back_offset: usize,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    front_offset: usize,
+    back: Link<T>,
+    back_offset: usize,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+pub struct IntoIter<T> {
+    list: UnrolledList<T>,
+}
+
+// cursor的位置由节点和节点内的下标共同决定，node为None时指向幽灵位置
+pub struct Cursor<'a, T> {
+    node: Link<T>,
+    offset: usize,
+    list: &'a UnrolledList<T>,
+    index: Option<usize>,
+}
+
+pub struct CursorMut<'a, T> {
+    node: Link<T>,
+    offset: usize,
+    list: &'a mut UnrolledList<T>,
+    index: Option<usize>,
+}
+
+impl<T> Node<T> {
+    fn alloc() -> NonNull<Node<T>> {
+        let node = Box::new(Node {
+            front: None,
+            back: None,
+            len: 0,
+            elems: [const { MaybeUninit::uninit() }; NODE_CAP],
+        });
+        NonNull::from(Box::leak(node))
+    }
+
+    fn ptr(&mut self) -> *mut T {
+        self.elems.as_mut_ptr() as *mut T
+    }
+
+    unsafe fn get<'a>(node: NonNull<Node<T>>, offset: usize) -> &'a T {
+        (*node.as_ptr()).elems[offset].assume_init_ref()
+    }
+
+    unsafe fn get_mut<'a>(node: NonNull<Node<T>>, offset: usize) -> &'a mut T {
+        (*node.as_ptr()).elems[offset].assume_init_mut()
+    }
+
+    // 在offset处插入，后面的元素整体后移一位，调用者保证节点没满
+    unsafe fn insert(&mut self, offset: usize, elem: T) {
+        debug_assert!(self.len < NODE_CAP && offset <= self.len);
+        let p = self.ptr().add(offset);
+        ptr::copy(p, p.add(1), self.len - offset);
+        ptr::write(p, elem);
+        self.len += 1;
+    }
+
+    // 取出offset处的元素，后面的元素整体前移一位
+    unsafe fn remove(&mut self, offset: usize) -> T {
+        debug_assert!(offset < self.len);
+        let p = self.ptr().add(offset);
+        let elem = ptr::read(p);
+        ptr::copy(p.add(1), p, self.len - offset - 1);
+        self.len -= 1;
+        elem
+    }
+
+    // 把elems[from..len]按顺序移到dst的末尾，调用者保证dst放得下
+    unsafe fn move_to(&mut self, from: usize, dst: &mut Node<T>) {
+        let count = self.len - from;
+        debug_assert!(dst.len + count <= NODE_CAP);
+        ptr::copy_nonoverlapping(self.ptr().add(from), dst.ptr().add(dst.len), count);
+        self.len = from;
+        dst.len += count;
+    }
+}
+
+// 供linkedlist::unlink读写节点的前后指针
+struct NodeOps<T>(PhantomData<T>);
+
+impl<T> LinkOps for NodeOps<T> {
+    type Node = Node<T>;
+
+    unsafe fn front(&self, node: NonNull<Node<T>>) -> Link<T> {
+        (*node.as_ptr()).front
+    }
+
+    unsafe fn back(&self, node: NonNull<Node<T>>) -> Link<T> {
+        (*node.as_ptr()).back
+    }
+
+    unsafe fn set_front(&self, node: NonNull<Node<T>>, link: Link<T>) {
+        (*node.as_ptr()).front = link;
+    }
+
+    unsafe fn set_back(&self, node: NonNull<Node<T>>, link: Link<T>) {
+        (*node.as_ptr()).back = link;
+    }
+}
+
+impl<T> UnrolledList<T> {
+    pub fn new() -> Self {
+        UnrolledList {
+            front: None,
+            back: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { Some(Node::get(self.front?, 0)) }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { Some(Node::get_mut(self.front?, 0)) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe {
+            let back = self.back?;
+            Some(Node::get(back, (*back.as_ptr()).len - 1))
+        }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe {
+            let back = self.back?;
+            Some(Node::get_mut(back, (*back.as_ptr()).len - 1))
+        }
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        unsafe {
+            match self.front {
+                Some(front) => {
+                    self.insert_at(front, 0, elem);
+                }
+                None => self.push_first(elem),
+            }
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        unsafe {
+            match self.back {
+                Some(back) => {
+                    self.insert_at(back, (*back.as_ptr()).len, elem);
+                }
+                None => self.push_first(elem),
+            }
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        unsafe {
+            let front = self.front?;
+            Some(self.remove_at(front, 0).0)
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        unsafe {
+            let back = self.back?;
+            Some(self.remove_at(back, (*back.as_ptr()).len - 1).0)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 整个节点一起释放，不需要像pop_front那样逐个移动元素
+    pub fn clear(&mut self) {
+        let mut cur = self.front.take();
+        self.back = None;
+        self.len = 0;
+        while let Some(node) = cur {
+            unsafe {
+                let mut node = Box::from_raw(node.as_ptr());
+                cur = node.back;
+                let len = node.len;
+                node.len = 0;
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(node.ptr(), len));
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.front,
+            front_offset: 0,
+            back: self.back,
+            back_offset: self.back.map_or(0, |node| unsafe { (*node.as_ptr()).len }),
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.front,
+            front_offset: 0,
+            back: self.back,
+            back_offset: self.back.map_or(0, |node| unsafe { (*node.as_ptr()).len }),
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    // 只读cursor，指向第一个元素，链表为空时指向幽灵位置
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            node: self.front,
+            offset: 0,
+            list: self,
+            index: if self.front.is_some() { Some(0) } else { None },
+        }
+    }
+
+    // 只读cursor，指向最后一个元素
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor {
+            node: self.back,
+            offset: self.back.map_or(0, |node| unsafe { (*node.as_ptr()).len - 1 }),
+            list: self,
+            index: self.len.checked_sub(1),
+        }
+    }
+
+    // 指向幽灵位置的cursor
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            node: None,
+            offset: 0,
+            list: self,
+            index: None,
+        }
+    }
+
+    // 返回指向第at个元素的cursor，at == len 时指向幽灵位置
+    // 按节点跳跃，从离at更近的一端开始，只需要经过 len / NODE_CAP 个节点左右
+    fn cursor_at(&mut self, at: usize) -> CursorMut<'_, T> {
+        let (node, offset) = if at < self.len {
+            unsafe { self.locate(at) }
+        } else {
+            (None, 0)
+        };
+        CursorMut {
+            node,
+            offset,
+            index: node.map(|_| at),
+            list: self,
+        }
+    }
+
+    // 把other的所有节点接到链表尾部，O(1)，other变为空链表
+    pub fn append(&mut self, other: &mut Self) {
+        let (front, back, len) = (other.front.take(), other.back.take(), other.len);
+        other.len = 0;
+        let front = match front {
+            Some(front) => front,
+            None => return,
+        };
+        unsafe {
+            if let Some(old) = self.back {
+                (*old.as_ptr()).back = Some(front);
+                (*front.as_ptr()).front = Some(old);
+            } else {
+                self.front = Some(front);
+            }
+        }
+        self.back = back;
+        self.len += len;
+    }
+
+    // 在at处切开，返回 [at, len) 的部分，自身保留 [0, at)
+    pub fn split_off(&mut self, at: usize) -> UnrolledList<T> {
+        assert!(at <= self.len, "Cannot split off at a nonexistent index");
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        self.cursor_at(at - 1).split_after()
+    }
+
+    // 在at处插入元素，原来at及之后的元素往后移
+    pub fn insert(&mut self, at: usize, elem: T) {
+        assert!(at <= self.len, "Cannot insert at a nonexistent index");
+        self.cursor_at(at).insert_before(elem);
+    }
+
+    // 删除at处的元素并返回
+    pub fn remove(&mut self, at: usize) -> T {
+        assert!(at < self.len, "Cannot remove at an index outside of the list bounds");
+        self.cursor_at(at).remove_current().unwrap()
+    }
+
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq<T>,
+    {
+        self.iter().any(|e| e == x)
+    }
+
+    // 下面是节点级别的操作，cursor和整体操作都建立在它们之上
+
+    unsafe fn push_first(&mut self, elem: T) {
+        let node = Node::alloc();
+        (*node.as_ptr()).insert(0, elem);
+        self.front = Some(node);
+        self.back = Some(node);
+        self.len = 1;
+    }
+
+    // 第at个元素所在的节点和下标，调用者保证 at < len
+    unsafe fn locate(&self, at: usize) -> (Link<T>, usize) {
+        if at < self.len / 2 {
+            let mut node = self.front.unwrap();
+            let mut rest = at;
+            while rest >= (*node.as_ptr()).len {
+                rest -= (*node.as_ptr()).len;
+                node = (*node.as_ptr()).back.unwrap();
+            }
+            (Some(node), rest)
+        } else {
+            // 从尾部往前数，rest是到最后一个元素的距离
+            let mut node = self.back.unwrap();
+            let mut rest = self.len - 1 - at;
+            while rest >= (*node.as_ptr()).len {
+                rest -= (*node.as_ptr()).len;
+                node = (*node.as_ptr()).front.unwrap();
+            }
+            (Some(node), (*node.as_ptr()).len - 1 - rest)
+        }
+    }
+
+    // 在node之后（after为None时在链表头部）接上一个新的空节点
+    unsafe fn link_after(&mut self, after: Link<T>) -> NonNull<Node<T>> {
+        let new = Node::alloc();
+        let next = match after {
+            Some(after) => (*after.as_ptr()).back.replace(new),
+            None => self.front.replace(new),
+        };
+        (*new.as_ptr()).front = after;
+        (*new.as_ptr()).back = next;
+        match next {
+            Some(next) => (*next.as_ptr()).front = Some(new),
+            None => self.back = Some(new),
+        }
+        new
+    }
+
+    // 摘下并释放一个已经没有元素的节点
+    unsafe fn free_node(&mut self, node: NonNull<Node<T>>) {
+        debug_assert_eq!((*node.as_ptr()).len, 0);
+        unlink(&NodeOps(PhantomData), node, &mut self.front, &mut self.back);
+        drop(Box::from_raw(node.as_ptr()));
+    }
+
+    // 把elem插到node的offset处，返回新元素所在的节点和下标
+    // 节点满了的时候：插在两端就放进相邻节点或者新节点，插在中间就把节点对半拆开
+    unsafe fn insert_at(&mut self, node: NonNull<Node<T>>, offset: usize, elem: T) -> (NonNull<Node<T>>, usize) {
+        self.len += 1;
+        let (target, offset) = if (*node.as_ptr()).len < NODE_CAP {
+            (node, offset)
+        } else if offset == 0 {
+            match (*node.as_ptr()).front {
+                Some(prev) if (*prev.as_ptr()).len < NODE_CAP => (prev, (*prev.as_ptr()).len),
+                prev => (self.link_after(prev), 0),
+            }
+        } else if offset == NODE_CAP {
+            match (*node.as_ptr()).back {
+                Some(next) if (*next.as_ptr()).len < NODE_CAP => (next, 0),
+                _ => (self.link_after(Some(node)), 0),
+            }
+        } else {
+            let half = NODE_CAP / 2;
+            let new = self.link_after(Some(node));
+            (*node.as_ptr()).move_to(half, &mut *new.as_ptr());
+            if offset <= half {
+                (node, offset)
+            } else {
+                (new, offset - half)
+            }
+        };
+        (*target.as_ptr()).insert(offset, elem);
+        (target, offset)
+    }
+
+    // 取出node中offset处的元素，返回它和下一个元素的位置（None表示幽灵位置）
+    // 节点空了就释放；和相邻节点加起来不到半满时合并，避免留下大量几乎为空的节点
+    unsafe fn remove_at(&mut self, node: NonNull<Node<T>>, offset: usize) -> (T, Link<T>, usize) {
+        let elem = (*node.as_ptr()).remove(offset);
+        self.len -= 1;
+
+        let len = (*node.as_ptr()).len;
+        let mut next = if offset < len {
+            (Some(node), offset)
+        } else {
+            ((*node.as_ptr()).back, 0)
+        };
+        if len == 0 {
+            self.free_node(node);
+            return (elem, next.0, next.1);
+        }
+        if let Some(back) = (*node.as_ptr()).back {
+            if len + (*back.as_ptr()).len <= NODE_CAP / 2 {
+                self.merge(node, back, &mut next);
+            }
+        }
+        if let Some(front) = (*node.as_ptr()).front {
+            if (*node.as_ptr()).len + (*front.as_ptr()).len <= NODE_CAP / 2 {
+                self.merge(front, node, &mut next);
+            }
+        }
+        (elem, next.0, next.1)
+    }
+
+    // 把right的元素全部移到left末尾并释放right，如果pos在right中，相应地改为left中的位置
+    unsafe fn merge(&mut self, left: NonNull<Node<T>>, right: NonNull<Node<T>>, pos: &mut (Link<T>, usize)) {
+        let left_len = (*left.as_ptr()).len;
+        (*right.as_ptr()).move_to(0, &mut *left.as_ptr());
+        if pos.0 == Some(right) {
+            *pos = (Some(left), left_len + pos.1);
+        }
+        self.free_node(right);
+    }
+
+    // (node, offset)的下一个位置，幽灵位置的下一个是第一个元素
+    unsafe fn next_pos(&self, node: Link<T>, offset: usize) -> (Link<T>, usize) {
+        match node {
+            Some(node) if offset + 1 < (*node.as_ptr()).len => (Some(node), offset + 1),
+            Some(node) => ((*node.as_ptr()).back, 0),
+            None => (self.front, 0),
+        }
+    }
+
+    // next_pos的镜像操作
+    unsafe fn prev_pos(&self, node: Link<T>, offset: usize) -> (Link<T>, usize) {
+        let prev = match node {
+            Some(node) if offset > 0 => return (Some(node), offset - 1),
+            Some(node) => (*node.as_ptr()).front,
+            None => self.back,
+        };
+        (prev, prev.map_or(0, |prev| (*prev.as_ptr()).len - 1))
+    }
+}
+
+impl<T> Default for UnrolledList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for UnrolledList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for UnrolledList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for UnrolledList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for UnrolledList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for UnrolledList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for UnrolledList<T> {}
+
+impl<T: Hash> Hash for UnrolledList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a UnrolledList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        unsafe {
+            let node = self.front?;
+            let elem = Node::get(node, self.front_offset);
+            self.front_offset += 1;
+            // 当前节点走完了，移到下一个节点
+            if self.front_offset == (*node.as_ptr()).len {
+                self.front = (*node.as_ptr()).back;
+                self.front_offset = 0;
+            }
+            self.len -= 1;
+            Some(elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        unsafe {
+            let mut node = self.back?;
+            if self.back_offset == 0 {
+                node = (*node.as_ptr()).front?;
+                self.back = Some(node);
+                self.back_offset = (*node.as_ptr()).len;
+            }
+            self.back_offset -= 1;
+            self.len -= 1;
+            Some(Node::get(node, self.back_offset))
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a mut UnrolledList<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// 和Iter相同，只是返回可变引用；len保证前后两端不会返回同一个元素
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        unsafe {
+            let node = self.front?;
+            let elem = Node::get_mut(node, self.front_offset);
+            self.front_offset += 1;
+            if self.front_offset == (*node.as_ptr()).len {
+                self.front = (*node.as_ptr()).back;
+                self.front_offset = 0;
+            }
+            self.len -= 1;
+            Some(elem)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        unsafe {
+            let mut node = self.back?;
+            if self.back_offset == 0 {
+                node = (*node.as_ptr()).front?;
+                self.back = Some(node);
+                self.back_offset = (*node.as_ptr()).len;
+            }
+            self.back_offset -= 1;
+            self.len -= 1;
+            Some(Node::get_mut(node, self.back_offset))
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<T> IntoIterator for UnrolledList<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> Drop for UnrolledList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+unsafe impl<T: Send> Send for UnrolledList<T> {}
+unsafe impl<T: Sync> Sync for UnrolledList<T> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Send for Cursor<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Cursor<'a, T> {}
+
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Cursor {
+            node: self.node,
+            offset: self.offset,
+            list: self.list,
+            index: self.index,
+        }
+    }
+}
+
+// 和linkedlist::Cursor的语义相同：在元素之间移动，最后一个元素和第一个元素之间是幽灵位置
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        unsafe {
+            (self.node, self.offset) = self.list.next_pos(self.node, self.offset);
+        }
+        self.index = match (self.node, self.index) {
+            (None, _) => None,
+            (Some(_), Some(index)) => Some(index + 1),
+            (Some(_), None) => Some(0),
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        unsafe {
+            (self.node, self.offset) = self.list.prev_pos(self.node, self.offset);
+        }
+        self.index = match (self.node, self.index) {
+            (None, _) => None,
+            (Some(_), Some(index)) => Some(index - 1),
+            (Some(_), None) => Some(self.list.len - 1),
+        };
+    }
+
+    // 返回的引用生命周期和链表相同
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { Some(Node::get(self.node?, self.offset)) }
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe {
+            let (node, offset) = self.list.next_pos(self.node, self.offset);
+            Some(Node::get(node?, offset))
+        }
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe {
+            let (node, offset) = self.list.prev_pos(self.node, self.offset);
+            Some(Node::get(node?, offset))
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    // 以只读cursor的形式查看当前位置
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            node: self.node,
+            offset: self.offset,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        let mut cursor = self.as_cursor();
+        cursor.move_next();
+        (self.node, self.offset, self.index) = (cursor.node, cursor.offset, cursor.index);
+    }
+
+    pub fn move_prev(&mut self) {
+        let mut cursor = self.as_cursor();
+        cursor.move_prev();
+        (self.node, self.offset, self.index) = (cursor.node, cursor.offset, cursor.index);
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { Some(Node::get_mut(self.node?, self.offset)) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let (node, offset) = self.list.next_pos(self.node, self.offset);
+            Some(Node::get_mut(node?, offset))
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let (node, offset) = self.list.prev_pos(self.node, self.offset);
+            Some(Node::get_mut(node?, offset))
+        }
+    }
+
+    // 插在当前元素前面；在幽灵位置时插到链表尾部
+    pub fn insert_before(&mut self, elem: T) {
+        let node = match self.node {
+            Some(node) => node,
+            None => return self.list.push_back(elem),
+        };
+        unsafe {
+            let new = self.list.insert_at(node, self.offset, elem);
+            // 插入可能拆分节点，当前元素就是新元素的下一个
+            (self.node, self.offset) = self.list.next_pos(Some(new.0), new.1);
+        }
+        *self.index.as_mut().unwrap() += 1;
+    }
+
+    // 插在当前元素后面；在幽灵位置时插到链表头部
+    pub fn insert_after(&mut self, elem: T) {
+        let node = match self.node {
+            Some(node) => node,
+            None => return self.list.push_front(elem),
+        };
+        unsafe {
+            let new = self.list.insert_at(node, self.offset + 1, elem);
+            (self.node, self.offset) = self.list.prev_pos(Some(new.0), new.1);
+        }
+    }
+
+    // 删除当前元素，cursor移到下一个元素，index不变
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.node?;
+        let (elem, next, offset) = unsafe { self.list.remove_at(node, self.offset) };
+        self.node = next;
+        self.offset = offset;
+        if self.node.is_none() {
+            self.index = None;
+        }
+        Some(elem)
+    }
+
+    // 返回当前元素之前的所有元素，链表只保留当前元素及其之后的部分
+    // 在幽灵位置时返回整个链表
+    pub fn split_before(&mut self) -> UnrolledList<T> {
+        let (node, index) = match (self.node, self.index) {
+            (Some(node), Some(index)) => (node, index),
+            _ => return std::mem::take(self.list),
+        };
+        unsafe {
+            // 当前元素不在节点开头时，先把它和之后的元素移到一个新节点
+            let first = if self.offset > 0 {
+                let new = self.list.link_after(Some(node));
+                (*node.as_ptr()).move_to(self.offset, &mut *new.as_ptr());
+                new
+            } else {
+                node
+            };
+            let output = self.list.detach_before(first, index);
+            self.node = Some(first);
+            self.offset = 0;
+            self.index = Some(0);
+            output
+        }
+    }
+
+    // 返回当前元素之后的所有元素，在幽灵位置时返回整个链表
+    pub fn split_after(&mut self) -> UnrolledList<T> {
+        let (node, index) = match (self.node, self.index) {
+            (Some(node), Some(index)) => (node, index),
+            _ => return std::mem::take(self.list),
+        };
+        unsafe {
+            if self.offset + 1 < (*node.as_ptr()).len {
+                let new = self.list.link_after(Some(node));
+                (*node.as_ptr()).move_to(self.offset + 1, &mut *new.as_ptr());
+            }
+            self.list.detach_after(node, index + 1)
+        }
+    }
+}
+
+impl<T> UnrolledList<T> {
+    // 在first节点前面切开，返回first之前的部分，count是这部分的元素个数
+    unsafe fn detach_before(&mut self, first: NonNull<Node<T>>, count: usize) -> UnrolledList<T> {
+        let mut output = UnrolledList::new();
+        if let Some(prev) = (*first.as_ptr()).front.take() {
+            (*prev.as_ptr()).back = None;
+            output.front = self.front.replace(first);
+            output.back = Some(prev);
+            output.len = count;
+            self.len -= count;
+        }
+        output
+    }
+
+    // 在last节点后面切开，返回last之后的部分，count是保留下来的元素个数
+    unsafe fn detach_after(&mut self, last: NonNull<Node<T>>, count: usize) -> UnrolledList<T> {
+        let mut output = UnrolledList::new();
+        if let Some(next) = (*last.as_ptr()).back.take() {
+            (*next.as_ptr()).front = None;
+            output.front = Some(next);
+            output.back = self.back.replace(last);
+            output.len = self.len - count;
+            self.len = count;
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{UnrolledList, NODE_CAP};
+
+    // 检查节点之间的指针和每个节点的元素个数
+    fn check_links<T>(list: &UnrolledList<T>) {
+        let mut prev = None;
+        let mut cur = list.front;
+        let mut len = 0;
+        unsafe {
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).front, prev);
+                let node_len = (*node.as_ptr()).len;
+                assert!(node_len > 0 && node_len <= NODE_CAP);
+                len += node_len;
+                prev = cur;
+                cur = (*node.as_ptr()).back;
+            }
+        }
+        assert_eq!(list.back, prev);
+        assert_eq!(list.len, len);
+    }
+
+    fn list_from(v: &[i32]) -> UnrolledList<i32> {
+        v.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut list = UnrolledList::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        for i in 0..100 {
+            list.push_back(i);
+            list.push_front(-i);
+        }
+        check_links(&list);
+        assert_eq!(list.len(), 200);
+        assert_eq!(list.front(), Some(&-99));
+        assert_eq!(list.back(), Some(&99));
+
+        *list.front_mut().unwrap() = 1000;
+        *list.back_mut().unwrap() = 2000;
+        assert_eq!(list.pop_front(), Some(1000));
+        assert_eq!(list.pop_back(), Some(2000));
+
+        for i in (0..99).rev() {
+            assert_eq!(list.pop_back(), Some(i));
+            assert_eq!(list.pop_front(), Some(-i));
+            check_links(&list);
+        }
+        assert!(list.is_empty());
+        assert_eq!(list.front(), None);
+        assert_eq!(list.back(), None);
+    }
+
+    #[test]
+    fn test_iterator() {
+        let v: Vec<i32> = (0..50).collect();
+        let mut list = list_from(&v);
+        assert_eq!(list.iter().len(), 50);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), v);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), v.iter().rev().copied().collect::<Vec<_>>());
+
+        // 两端交替，在中间相遇时不会重复返回
+        let mut iter = list.iter_mut();
+        for i in 0..25 {
+            *iter.next().unwrap() += 100;
+            assert_eq!(*iter.next_back().unwrap(), 49 - i);
+        }
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+
+        let mut into_iter = list.clone().into_iter();
+        assert_eq!(into_iter.len(), 50);
+        assert_eq!(into_iter.next(), Some(100));
+        assert_eq!(into_iter.next_back(), Some(49));
+        assert_eq!(into_iter.len(), 48);
+
+        for elem in &mut list {
+            *elem = -*elem;
+        }
+        assert_eq!(list.back(), Some(&-49));
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut list = UnrolledList::new();
+        let mut expected = Vec::new();
+        // 固定的伪随机位置，覆盖节点满了以后在头、中、尾插入的各种情况
+        let mut seed = 12345u32;
+        for i in 0..500 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let at = (seed >> 16) as usize % (expected.len() + 1);
+            list.insert(at, i);
+            expected.insert(at, i);
+        }
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+
+        while !expected.is_empty() {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let at = (seed >> 16) as usize % expected.len();
+            assert_eq!(list.remove(at), expected.remove(at));
+            check_links(&list);
+        }
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_cursor() {
+        let list = list_from(&[1, 2, 3]);
+        let mut cursor = list.cursor_front();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_prev(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.peek_next(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&3));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&3));
+
+        let back = list.cursor_back();
+        assert_eq!(back.current(), Some(&3));
+        assert_eq!(back.index(), Some(2));
+        assert_eq!(UnrolledList::<i32>::new().cursor_back().index(), None);
+    }
+
+    #[test]
+    fn test_cursor_mut() {
+        let mut list: UnrolledList<i32> = (0..40).collect();
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        // 在满节点的中间插入，cursor仍然指向原来的元素
+        for _ in 0..10 {
+            cursor.move_next();
+        }
+        assert_eq!(cursor.current(), Some(&mut 10));
+        cursor.insert_before(-1);
+        cursor.insert_after(-2);
+        assert_eq!(cursor.current(), Some(&mut 10));
+        assert_eq!(cursor.index(), Some(11));
+        assert_eq!(cursor.peek_prev(), Some(&mut -1));
+        assert_eq!(cursor.peek_next(), Some(&mut -2));
+
+        assert_eq!(cursor.remove_current(), Some(10));
+        assert_eq!(cursor.current(), Some(&mut -2));
+        assert_eq!(cursor.index(), Some(11));
+        assert_eq!(cursor.as_cursor().peek_prev(), Some(&-1));
+        check_links(&list);
+
+        let mut expected: Vec<i32> = (0..40).collect();
+        expected[10] = -2;
+        expected.insert(10, -1);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+
+        // 在幽灵位置插入
+        let mut cursor = list.cursor_mut();
+        cursor.insert_before(100);
+        cursor.insert_after(-100);
+        assert_eq!(list.front(), Some(&-100));
+        assert_eq!(list.back(), Some(&100));
+
+        // 删除最后一个元素后回到幽灵位置
+        let mut cursor = list.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(100));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+    }
+
+    #[test]
+    fn test_split() {
+        for at in [0, 1, 7, 16, 17, 31, 32, 45] {
+            let v: Vec<i32> = (0..45).collect();
+            let mut list = list_from(&v);
+            let tail = list.split_off(at);
+            check_links(&list);
+            check_links(&tail);
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), &v[..at]);
+            assert_eq!(tail.iter().copied().collect::<Vec<_>>(), &v[at..]);
+        }
+
+        let mut list: UnrolledList<i32> = (0..40).collect();
+        let mut cursor = list.cursor_mut();
+        for _ in 0..21 {
+            cursor.move_next();
+        }
+        let before = cursor.split_before();
+        assert_eq!(cursor.current(), Some(&mut 20));
+        assert_eq!(cursor.index(), Some(0));
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&before);
+        check_links(&after);
+        check_links(&list);
+        assert_eq!(before.iter().copied().collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [20]);
+        assert_eq!(after.iter().copied().collect::<Vec<_>>(), (21..40).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_off_panic() {
+        let mut list = list_from(&[1, 2]);
+        list.split_off(3);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut a: UnrolledList<i32> = (0..20).collect();
+        let mut b: UnrolledList<i32> = (20..30).collect();
+        a.append(&mut b);
+        check_links(&a);
+        assert!(b.is_empty());
+        assert_eq!(a.len(), 30);
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), (0..30).collect::<Vec<_>>());
+
+        b.append(&mut a);
+        assert_eq!(b.len(), 30);
+        assert!(a.is_empty());
+        b.append(&mut a);
+        b.push_back(30);
+        check_links(&b);
+        assert!(b.contains(&30));
+        assert!(!b.contains(&31));
+    }
+
+    #[test]
+    fn test_traits() {
+        let list = list_from(&[1, 2, 3]);
+        let copy = list.clone();
+        assert_eq!(list, copy);
+        assert_eq!(format!("{:?}", copy), "[1, 2, 3]");
+        assert_ne!(list, list_from(&[1, 2]));
+        assert_eq!(UnrolledList::<i32>::default(), UnrolledList::new());
+    }
+
+    #[test]
+    fn test_drop() {
+        use std::rc::Rc;
+
+        let counter = Rc::new(());
+        let mut list: UnrolledList<Rc<()>> = (0..100).map(|_| counter.clone()).collect();
+        list.split_off(50);
+        drop(list.pop_front());
+        assert_eq!(Rc::strong_count(&counter), 50);
+        list.clear();
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
@@ -0,0 +1,1621 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+use std::vec;
+
+// 默认的最小度数，和std一样每个节点最多11个key、12个子节点
+const DEFAULT_MIN_DEGREE: usize = 6;
+
+// B树：每个节点保存一段有序的key，内部节点的第i个子树里的key都在keys[i-1]和keys[i]之间。
+// 插入时沿路把满的节点提前分裂，删除时沿路把只有b-1个key的节点提前补足（借一个或者合并），
+// 这样从根往下走一遍就能完成修改，不需要再回头调整父节点。
+#[derive(Clone)]
+pub struct BTreeMap<K, V> {
+    root: Option<Node<K, V>>,
+    len: usize,
+    // 最小度数b：除了根节点，每个节点有b-1..=2b-1个key
+    b: usize,
+}
+
+#[derive(Clone)]
+struct Node<K, V> {
+    keys: Vec<K>,
+    vals: Vec<V>,
+    // 叶子节点为空，内部节点的子节点比key多一个
+    children: Vec<Node<K, V>>,
+}
+
+// 节点在树中的位置，遍历时用来判断两端是否相遇
+type Pos<K, V> = (NonNull<Node<K, V>>, usize);
+
+// 中序遍历的游标，栈里保存从根到当前位置的路径
+// 游标不借用树，Range和RangeMut可以同时从两端移动，生命周期由外层的PhantomData保证
+struct Cursor<K, V> {
+    stack: Vec<Pos<K, V>>,
+}
+
+// 两个方向的游标，front_stop/back_stop是对方已经访问过的第一个位置，走到那里就结束
+struct RawRange<K, V> {
+    front: Cursor<K, V>,
+    back: Cursor<K, V>,
+    front_stop: Option<Pos<K, V>>,
+    back_stop: Option<Pos<K, V>>,
+    done: bool,
+}
+
+pub struct Range<'a, K, V> {
+    raw: RawRange<K, V>,
+    _boo: PhantomData<&'a Node<K, V>>,
+}
+
+pub struct RangeMut<'a, K, V> {
+    raw: RawRange<K, V>,
+    _boo: PhantomData<&'a mut Node<K, V>>,
+}
+
+pub struct Iter<'a, K, V> {
+    range: Range<'a, K, V>,
+    len: usize,
+}
+
+pub struct IterMut<'a, K, V> {
+    range: RangeMut<'a, K, V>,
+    len: usize,
+}
+
+pub struct IntoIter<K, V>(vec::IntoIter<(K, V)>);
+
+pub enum Entry<'a, K, V> {
+    Vacant(VacantEntry<'a, K, V>),
+    Occupied(OccupiedEntry<'a, K, V>),
+}
+
+pub struct VacantEntry<'a, K, V> {
+    map: &'a mut BTreeMap<K, V>,
+    key: K,
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    map: &'a mut BTreeMap<K, V>,
+    key: K,
+}
+
+// 高度为height的子树最多能放的key个数：(2b)^(height+1) - 1
+fn max_keys(height: usize, b: usize) -> usize {
+    (2 * b).saturating_pow(height as u32 + 1) - 1
+}
+
+// 根节点没有key时去掉根节点，树的高度减一
+fn fix_top<K, V>(root: &mut Option<Node<K, V>>) {
+    while root.as_ref().is_some_and(|node| node.keys.is_empty()) {
+        *root = root.take().unwrap().children.pop();
+    }
+}
+
+fn search<K: Borrow<Q>, Q: Ord + ?Sized>(keys: &[K], key: &Q) -> Result<usize, usize> {
+    keys.binary_search_by(|k| k.borrow().cmp(key))
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Self {
+        Node {
+            keys: Vec::new(),
+            vals: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    // 把满的第i个子节点（2b-1个key）从中间分开，中间的key上移到当前节点
+    fn split_child(&mut self, i: usize, b: usize) {
+        let child = &mut self.children[i];
+        let mut right = Node::new();
+        right.keys = child.keys.split_off(b);
+        right.vals = child.vals.split_off(b);
+        if !child.is_leaf() {
+            right.children = child.children.split_off(b);
+        }
+        let key = child.keys.pop().unwrap();
+        let val = child.vals.pop().unwrap();
+        self.keys.insert(i, key);
+        self.vals.insert(i, val);
+        self.children.insert(i + 1, right);
+    }
+
+    // 把第i+1个子节点和中间的key一起并入第i个子节点
+    fn merge_children(&mut self, i: usize) {
+        let right = self.children.remove(i + 1);
+        let key = self.keys.remove(i);
+        let val = self.vals.remove(i);
+        let left = &mut self.children[i];
+        left.keys.push(key);
+        left.keys.extend(right.keys);
+        left.vals.push(val);
+        left.vals.extend(right.vals);
+        left.children.extend(right.children);
+    }
+
+    // 从左兄弟借一个：左兄弟最大的key上移到当前节点，原来的分隔key下移到第i个子节点的最前面
+    fn rotate_right(&mut self, i: usize) {
+        let (left, right) = self.children.split_at_mut(i);
+        let (left, child) = (&mut left[i - 1], &mut right[0]);
+        let key = mem::replace(&mut self.keys[i - 1], left.keys.pop().unwrap());
+        let val = mem::replace(&mut self.vals[i - 1], left.vals.pop().unwrap());
+        child.keys.insert(0, key);
+        child.vals.insert(0, val);
+        if let Some(grandchild) = left.children.pop() {
+            child.children.insert(0, grandchild);
+        }
+    }
+
+    // 镜像操作，从右兄弟借最小的key
+    fn rotate_left(&mut self, i: usize) {
+        let (left, right) = self.children.split_at_mut(i + 1);
+        let (child, right) = (&mut left[i], &mut right[0]);
+        let key = mem::replace(&mut self.keys[i], right.keys.remove(0));
+        let val = mem::replace(&mut self.vals[i], right.vals.remove(0));
+        child.keys.push(key);
+        child.vals.push(val);
+        if !right.is_leaf() {
+            child.children.push(right.children.remove(0));
+        }
+    }
+
+    // 保证第i个子节点至少有b个key，这样从它里面删掉一个之后仍然满足下限
+    // 先尝试从左右兄弟借一个，借不到就和兄弟合并，返回合并后要继续往下走的子节点下标
+    fn fill_child(&mut self, i: usize, b: usize) -> usize {
+        if self.children[i].keys.len() >= b {
+            return i;
+        }
+        if i > 0 && self.children[i - 1].keys.len() >= b {
+            self.rotate_right(i);
+            return i;
+        }
+        if i + 1 < self.children.len() && self.children[i + 1].keys.len() >= b {
+            self.rotate_left(i);
+            return i;
+        }
+        if i + 1 < self.children.len() {
+            self.merge_children(i);
+            i
+        } else {
+            self.merge_children(i - 1);
+            i - 1
+        }
+    }
+
+    // 删除子树中最小的元素，调用前当前节点至少有b个key（或者是根节点）
+    fn pop_first(&mut self, b: usize) -> (K, V) {
+        let mut node = self;
+        while !node.is_leaf() {
+            let i = node.fill_child(0, b);
+            node = &mut node.children[i];
+        }
+        (node.keys.remove(0), node.vals.remove(0))
+    }
+
+    // pop_first的镜像操作
+    fn pop_last(&mut self, b: usize) -> (K, V) {
+        let mut node = self;
+        while !node.is_leaf() {
+            let i = node.fill_child(node.children.len() - 1, b);
+            node = &mut node.children[i];
+        }
+        (node.keys.pop().unwrap(), node.vals.pop().unwrap())
+    }
+
+    fn remove<Q>(&mut self, key: &Q, b: usize) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self;
+        loop {
+            match search(&node.keys, key) {
+                Ok(i) => {
+                    if node.is_leaf() {
+                        return Some((node.keys.remove(i), node.vals.remove(i)));
+                    }
+                    // 内部节点的key用前驱或者后继替换，从子树里删除前驱或者后继
+                    if node.children[i].keys.len() >= b {
+                        let (k, v) = node.children[i].pop_last(b);
+                        return Some((
+                            mem::replace(&mut node.keys[i], k),
+                            mem::replace(&mut node.vals[i], v),
+                        ));
+                    }
+                    if node.children[i + 1].keys.len() >= b {
+                        let (k, v) = node.children[i + 1].pop_first(b);
+                        return Some((
+                            mem::replace(&mut node.keys[i], k),
+                            mem::replace(&mut node.vals[i], v),
+                        ));
+                    }
+                    // 两边都只有b-1个key，合并之后要删的key在子节点中间，继续往下删
+                    node.merge_children(i);
+                    node = &mut node.children[i];
+                }
+                Err(i) => {
+                    if node.is_leaf() {
+                        return None;
+                    }
+                    let i = node.fill_child(i, b);
+                    node = &mut node.children[i];
+                }
+            }
+        }
+    }
+
+    // 沿着key的查找路径把子树切成两棵同样高度的子树，当前节点保留小于key的部分，返回大于等于key的部分。
+    // 路径上被切开的节点分别在两棵树的右边界和左边界上，可能不满足大小下限，由fix_*_border修复
+    fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let i = self.keys.partition_point(|k| k.borrow() < key);
+        let mut right = Node::new();
+        right.keys = self.keys.split_off(i);
+        right.vals = self.vals.split_off(i);
+        if !self.is_leaf() {
+            right.children = self.children.split_off(i + 1);
+            let child = self.children[i].split_off(key);
+            right.children.insert(0, child);
+        }
+        right
+    }
+
+    // 从上往下修复右边界：往下走之前让最右边的子节点至少有b个key，
+    // 和左兄弟放得进一个节点就合并，否则从左兄弟借够b个，左兄弟不在边界上，借完仍然满足下限
+    fn fix_right_border(&mut self, b: usize) {
+        let mut node = self;
+        while !node.is_leaf() {
+            let last = node.children.len() - 1;
+            let len = node.children[last].keys.len();
+            if len < b {
+                if node.children[last - 1].keys.len() + len < 2 * b - 1 {
+                    node.merge_children(last - 1);
+                } else {
+                    for _ in len..b {
+                        node.rotate_right(last);
+                    }
+                }
+            }
+            node = node.children.last_mut().unwrap();
+        }
+    }
+
+    // fix_right_border的镜像操作
+    fn fix_left_border(&mut self, b: usize) {
+        let mut node = self;
+        while !node.is_leaf() {
+            let len = node.children[0].keys.len();
+            if len < b {
+                if node.children[1].keys.len() + len < 2 * b - 1 {
+                    node.merge_children(0);
+                } else {
+                    for _ in len..b {
+                        node.rotate_left(0);
+                    }
+                }
+            }
+            node = &mut node.children[0];
+        }
+    }
+
+    // 用有序的iter中的count个元素建一棵高度为height的子树，O(count)。
+    // 子节点个数取能放下所有元素的最小值（至少是下限），元素尽量平均分给各个子节点，
+    // 每个子节点的元素个数都在对应高度子树的上下限之间
+    fn build<I>(iter: &mut I, count: usize, height: usize, b: usize, is_root: bool) -> Self
+    where
+        I: Iterator<Item = (K, V)>,
+    {
+        let mut node = Node::new();
+        if height == 0 {
+            for (key, val) in iter.take(count) {
+                node.keys.push(key);
+                node.vals.push(val);
+            }
+            return node;
+        }
+        let child_max = max_keys(height - 1, b);
+        let min_children = if is_root { 2 } else { b };
+        let children = (count + 1)
+            .div_ceil(child_max.saturating_add(1))
+            .max(min_children);
+        let total = count - (children - 1);
+        let (size, extra) = (total / children, total % children);
+        for i in 0..children {
+            let child_count = size + usize::from(i < extra);
+            node.children
+                .push(Node::build(iter, child_count, height - 1, b, false));
+            if i + 1 < children {
+                let (key, val) = iter.next().unwrap();
+                node.keys.push(key);
+                node.vals.push(val);
+            }
+        }
+        node
+    }
+
+    fn height(&self) -> usize {
+        let mut node = self;
+        let mut height = 0;
+        while let Some(child) = node.children.first() {
+            node = child;
+            height += 1;
+        }
+        height
+    }
+
+    fn count(&self) -> usize {
+        self.keys.len() + self.children.iter().map(Node::count).sum::<usize>()
+    }
+
+    // 按顺序取出子树中的所有元素
+    fn drain_into(self, out: &mut Vec<(K, V)>) {
+        let mut children = self.children.into_iter();
+        for (key, val) in self.keys.into_iter().zip(self.vals) {
+            if let Some(child) = children.next() {
+                child.drain_into(out);
+            }
+            out.push((key, val));
+        }
+        if let Some(child) = children.next() {
+            child.drain_into(out);
+        }
+    }
+
+    // 下面几个函数只通过裸指针访问节点，不会创建对整个节点的引用，
+    // 所以RangeMut已经交出去的&mut V不会因为游标继续移动而失效
+
+    unsafe fn keys_of<'a>(node: NonNull<Self>) -> &'a [K] {
+        &(*node.as_ptr()).keys
+    }
+
+    unsafe fn is_leaf_ptr(node: NonNull<Self>) -> bool {
+        let children: &[Self] = &(*node.as_ptr()).children;
+        children.is_empty()
+    }
+
+    unsafe fn child_ptr(node: NonNull<Self>, i: usize) -> NonNull<Self> {
+        let children: &Vec<Self> = &(*node.as_ptr()).children;
+        NonNull::new_unchecked(children.as_ptr().add(i) as *mut Self)
+    }
+}
+
+impl<K, V> Cursor<K, V> {
+    // 定位到第一个满足下界的位置
+    unsafe fn seek_front<Q>(root: NonNull<Node<K, V>>, bound: Bound<&Q>) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut stack = Vec::new();
+        let mut node = root;
+        loop {
+            let keys = Node::keys_of(node);
+            let i = match bound {
+                Bound::Unbounded => 0,
+                Bound::Included(q) => keys.partition_point(|k| k.borrow() < q),
+                Bound::Excluded(q) => keys.partition_point(|k| k.borrow() <= q),
+            };
+            stack.push((node, i));
+            if Node::is_leaf_ptr(node) {
+                return Cursor { stack };
+            }
+            node = Node::child_ptr(node, i);
+        }
+    }
+
+    // 定位到最后一个满足上界的位置之后
+    unsafe fn seek_back<Q>(root: NonNull<Node<K, V>>, bound: Bound<&Q>) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut stack = Vec::new();
+        let mut node = root;
+        loop {
+            let keys = Node::keys_of(node);
+            let i = match bound {
+                Bound::Unbounded => keys.len(),
+                Bound::Included(q) => keys.partition_point(|k| k.borrow() <= q),
+                Bound::Excluded(q) => keys.partition_point(|k| k.borrow() < q),
+            };
+            stack.push((node, i));
+            if Node::is_leaf_ptr(node) {
+                return Cursor { stack };
+            }
+            node = Node::child_ptr(node, i);
+        }
+    }
+
+    // 正向：栈顶(node, i)表示下一个元素是node.keys[i]
+    unsafe fn next(&mut self) -> Option<Pos<K, V>> {
+        loop {
+            let (node, i) = *self.stack.last()?;
+            if i < Node::keys_of(node).len() {
+                self.stack.last_mut().unwrap().1 = i + 1;
+                // 内部节点的下一个元素在右边子树的最左边
+                if !Node::is_leaf_ptr(node) {
+                    let mut child = Node::child_ptr(node, i + 1);
+                    loop {
+                        self.stack.push((child, 0));
+                        if Node::is_leaf_ptr(child) {
+                            break;
+                        }
+                        child = Node::child_ptr(child, 0);
+                    }
+                }
+                return Some((node, i));
+            }
+            self.stack.pop();
+        }
+    }
+
+    // 反向：栈顶(node, i)表示下一个元素是node.keys[i - 1]
+    unsafe fn next_back(&mut self) -> Option<Pos<K, V>> {
+        loop {
+            let (node, i) = *self.stack.last()?;
+            if i > 0 {
+                self.stack.last_mut().unwrap().1 = i - 1;
+                if !Node::is_leaf_ptr(node) {
+                    let mut child = Node::child_ptr(node, i - 1);
+                    loop {
+                        self.stack.push((child, Node::keys_of(child).len()));
+                        if Node::is_leaf_ptr(child) {
+                            break;
+                        }
+                        child = Node::child_ptr(child, Node::keys_of(child).len());
+                    }
+                }
+                return Some((node, i - 1));
+            }
+            self.stack.pop();
+        }
+    }
+}
+
+fn flip<Q: ?Sized>(bound: Bound<&Q>) -> Bound<&Q> {
+    match bound {
+        Bound::Included(q) => Bound::Excluded(q),
+        Bound::Excluded(q) => Bound::Included(q),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl<K, V> RawRange<K, V> {
+    fn empty() -> Self {
+        RawRange {
+            front: Cursor { stack: Vec::new() },
+            back: Cursor { stack: Vec::new() },
+            front_stop: None,
+            back_stop: None,
+            done: true,
+        }
+    }
+
+    // 整棵树，不需要比较key
+    unsafe fn full(root: NonNull<Node<K, V>>) -> Self {
+        let mut front = Cursor {
+            stack: vec![(root, 0)],
+        };
+        let mut node = root;
+        while !Node::is_leaf_ptr(node) {
+            node = Node::child_ptr(node, 0);
+            front.stack.push((node, 0));
+        }
+        let mut back = Cursor {
+            stack: vec![(root, Node::keys_of(root).len())],
+        };
+        let mut node = root;
+        while !Node::is_leaf_ptr(node) {
+            node = Node::child_ptr(node, Node::keys_of(node).len());
+            back.stack.push((node, Node::keys_of(node).len()));
+        }
+        RawRange {
+            front,
+            back,
+            front_stop: None,
+            back_stop: None,
+            done: false,
+        }
+    }
+
+    unsafe fn new<Q, R>(root: NonNull<Node<K, V>>, range: &R) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        match (start, end) {
+            (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+                panic!("range start and end are equal and excluded in BTreeMap")
+            }
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+                if s > e =>
+            {
+                panic!("range start is greater than range end in BTreeMap")
+            }
+            _ => {}
+        }
+        // 正向走到上界之外的第一个元素就停，反向走到下界之前的最后一个元素就停
+        let front_stop = match end {
+            Bound::Unbounded => None,
+            _ => Cursor::seek_front(root, flip(end)).next(),
+        };
+        let back_stop = match start {
+            Bound::Unbounded => None,
+            _ => Cursor::seek_back(root, flip(start)).next_back(),
+        };
+        RawRange {
+            front: Cursor::seek_front(root, start),
+            back: Cursor::seek_back(root, end),
+            front_stop,
+            back_stop,
+            done: false,
+        }
+    }
+
+    fn next(&mut self) -> Option<Pos<K, V>> {
+        if self.done {
+            return None;
+        }
+        let pos = unsafe { self.front.next() };
+        if pos.is_none() || pos == self.front_stop {
+            self.done = true;
+            return None;
+        }
+        self.back_stop = pos;
+        pos
+    }
+
+    fn next_back(&mut self) -> Option<Pos<K, V>> {
+        if self.done {
+            return None;
+        }
+        let pos = unsafe { self.back.next_back() };
+        if pos.is_none() || pos == self.back_stop {
+            self.done = true;
+            return None;
+        }
+        self.front_stop = pos;
+        pos
+    }
+}
+
+impl<K, V> BTreeMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_min_degree(DEFAULT_MIN_DEGREE)
+    }
+
+    // b是最小度数，除了根节点每个节点有b..=2b个子节点，也就是b-1..=2b-1个key，b至少是2
+    pub fn with_min_degree(b: usize) -> Self {
+        assert!(b >= 2, "BTreeMap min degree must be at least 2");
+        BTreeMap {
+            root: None,
+            len: 0,
+            b,
+        }
+    }
+
+    pub fn min_degree(&self) -> usize {
+        self.b
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        Some((node.keys.first()?, node.vals.first()?))
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        Some((node.keys.last()?, node.vals.last()?))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let raw = match &self.root {
+            Some(root) => unsafe { RawRange::full(NonNull::from(root)) },
+            None => RawRange::empty(),
+        };
+        Iter {
+            range: Range {
+                raw,
+                _boo: PhantomData,
+            },
+            len: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let raw = match &mut self.root {
+            Some(root) => unsafe { RawRange::full(NonNull::from(root)) },
+            None => RawRange::empty(),
+        };
+        IterMut {
+            range: RangeMut {
+                raw,
+                _boo: PhantomData,
+            },
+            len: self.len,
+        }
+    }
+
+    // 按key的顺序遍历范围内的元素，两端都可以遍历
+    // 和std一样，start > end或者start == end并且两端都不包含时panic
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let raw = match &self.root {
+            Some(root) => unsafe { RawRange::new(NonNull::from(root), &range) },
+            None => RawRange::empty(),
+        };
+        Range {
+            raw,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let raw = match &mut self.root {
+            Some(root) => unsafe { RawRange::new(NonNull::from(root), &range) },
+            None => RawRange::empty(),
+        };
+        RangeMut {
+            raw,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<K: Ord, V> BTreeMap<K, V> {
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_ref()?;
+        loop {
+            match search(&node.keys, key) {
+                Ok(i) => return Some((&node.keys[i], &node.vals[i])),
+                Err(i) => node = node.children.get(i)?,
+            }
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_mut()?;
+        loop {
+            match search(&node.keys, key) {
+                Ok(i) => return Some(&mut node.vals[i]),
+                Err(i) => node = node.children.get_mut(i)?,
+            }
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // key已经存在时替换value并返回旧值，key保持不变
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_entry(key, value).1
+    }
+
+    // 插入并返回value所在的位置，Entry也用它
+    fn insert_entry(&mut self, key: K, value: V) -> (&mut V, Option<V>) {
+        let b = self.b;
+        let len = &mut self.len;
+        let root = self.root.get_or_insert_with(Node::new);
+        // 根节点满了就先分裂，树的高度加一
+        if root.keys.len() == 2 * b - 1 {
+            let old = mem::replace(root, Node::new());
+            root.children.push(old);
+            root.split_child(0, b);
+        }
+
+        let mut node = root;
+        loop {
+            let mut i = match search(&node.keys, &key) {
+                Ok(i) => {
+                    let old = mem::replace(&mut node.vals[i], value);
+                    return (&mut node.vals[i], Some(old));
+                }
+                Err(i) => i,
+            };
+            if node.is_leaf() {
+                node.keys.insert(i, key);
+                node.vals.insert(i, value);
+                *len += 1;
+                return (&mut node.vals[i], None);
+            }
+            // 往下走之前先把满的子节点分裂，保证插入叶子节点时一定有空位
+            if node.children[i].keys.len() == 2 * b - 1 {
+                node.split_child(i, b);
+                match key.cmp(&node.keys[i]) {
+                    Ordering::Less => {}
+                    Ordering::Equal => {
+                        let old = mem::replace(&mut node.vals[i], value);
+                        return (&mut node.vals[i], Some(old));
+                    }
+                    Ordering::Greater => i += 1,
+                }
+            }
+            node = &mut node.children[i];
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let b = self.b;
+        let root = self.root.as_mut()?;
+        let removed = root.remove(key, b);
+        // 根节点唯一的key被合并到子节点之后，树的高度减一
+        if root.keys.is_empty() {
+            self.root = root.children.pop();
+        }
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        if self.contains_key(&key) {
+            Entry::Occupied(OccupiedEntry { map: self, key })
+        } else {
+            Entry::Vacant(VacantEntry { map: self, key })
+        }
+    }
+
+    // 把大于等于key的元素分到返回的新map中。
+    // 只切开查找路径上的节点再修复两边的边界，O(b log n)；两边的元素个数需要数一遍其中较矮的一棵树
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let b = self.b;
+        let mut right = Self::with_min_degree(b);
+        let Some(root) = &mut self.root else {
+            return right;
+        };
+        right.root = Some(root.split_off(key));
+        fix_top(&mut self.root);
+        fix_top(&mut right.root);
+        if let Some(root) = &mut self.root {
+            root.fix_right_border(b);
+        }
+        if let Some(root) = &mut right.root {
+            root.fix_left_border(b);
+        }
+        fix_top(&mut self.root);
+        fix_top(&mut right.root);
+
+        let total = self.len;
+        let height = |root: &Option<Node<K, V>>| root.as_ref().map_or(0, Node::height);
+        if height(&self.root) <= height(&right.root) {
+            self.len = self.root.as_ref().map_or(0, Node::count);
+            right.len = total - self.len;
+        } else {
+            right.len = right.root.as_ref().map_or(0, Node::count);
+            self.len = total - right.len;
+        }
+        right
+    }
+
+    // 把other的所有元素移过来，key相同时用other的value，other变为空。
+    // 两边的元素已经有序，按顺序合并之后直接建一棵新树，O(n + m)
+    pub fn append(&mut self, other: &mut Self) {
+        if other.is_empty() {
+            return;
+        }
+        let b = self.b;
+        let mut left = mem::replace(self, Self::with_min_degree(b))
+            .into_iter()
+            .peekable();
+        let mut right = mem::replace(other, Self::with_min_degree(other.b))
+            .into_iter()
+            .peekable();
+
+        let mut merged = Vec::with_capacity(left.len() + right.len());
+        loop {
+            let take_left = match (left.peek(), right.peek()) {
+                (Some((a, _)), Some((b, _))) => match a.cmp(b) {
+                    Ordering::Less => true,
+                    Ordering::Greater => false,
+                    Ordering::Equal => {
+                        left.next();
+                        false
+                    }
+                },
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            merged.extend(if take_left { left.next() } else { right.next() });
+        }
+        *self = Self::from_sorted(b, merged);
+    }
+
+    // 用严格递增的元素直接建树
+    fn from_sorted(b: usize, entries: Vec<(K, V)>) -> Self {
+        let mut map = Self::with_min_degree(b);
+        map.len = entries.len();
+        if map.len > 0 {
+            let mut height = 0;
+            while max_keys(height, b) < map.len {
+                height += 1;
+            }
+            map.root = Some(Node::build(
+                &mut entries.into_iter(),
+                map.len,
+                height,
+                b,
+                true,
+            ));
+        }
+        map
+    }
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert_entry(self.key, value).0
+    }
+}
+
+// 只记录key，每次操作重新从根往下查找，O(log n)
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    // 和remove_entry一样返回map中原来的key，而不是传给entry()的key
+    pub fn key(&self) -> &K {
+        self.map.get_key_value(&self.key).unwrap().0
+    }
+
+    pub fn get(&self) -> &V {
+        self.map.get(&self.key).unwrap()
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.map.get_mut(&self.key).unwrap()
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        self.map.get_mut(&self.key).unwrap()
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    // 返回的是map中原来的key
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.remove_entry(&self.key).unwrap()
+    }
+}
+
+impl<K, V> Default for BTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for BTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for BTreeMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for BTreeMap<K, V> {}
+
+impl<K: Ord, V> Extend<(K, V)> for BTreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BTreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> IntoIterator for BTreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    // 先按顺序把所有元素取出来，之后的遍历就是Vec的遍历
+    fn into_iter(self) -> Self::IntoIter {
+        let mut out = Vec::with_capacity(self.len);
+        if let Some(root) = self.root {
+            root.drain_into(&mut out);
+        }
+        IntoIter(out.into_iter())
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a BTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut BTreeMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Range借用了整棵树，位置一定有效
+unsafe fn kv<'a, K, V>((node, i): Pos<K, V>) -> (&'a K, &'a V) {
+    let vals: &[V] = &(*node.as_ptr()).vals;
+    (&Node::keys_of(node)[i], &*vals.as_ptr().add(i))
+}
+
+// RangeMut独占整棵树，每个位置只会被交出去一次
+unsafe fn kv_mut<'a, K, V>((node, i): Pos<K, V>) -> (&'a K, &'a mut V) {
+    let vals: &mut Vec<V> = &mut (*node.as_ptr()).vals;
+    (&Node::keys_of(node)[i], &mut *vals.as_mut_ptr().add(i))
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next().map(|pos| unsafe { kv(pos) })
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.raw.next_back().map(|pos| unsafe { kv(pos) })
+    }
+}
+
+impl<'a, K, V> Iterator for RangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next().map(|pos| unsafe { kv_mut(pos) })
+    }
+}
+
+impl<K, V> DoubleEndedIterator for RangeMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.raw.next_back().map(|pos| unsafe { kv_mut(pos) })
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.range.next()?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.range.next_back()?;
+        self.len -= 1;
+        Some(item)
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.range.next()?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.range.next_back()?;
+        self.len -= 1;
+        Some(item)
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+// 游标里只有裸指针，线程安全性和对应的引用相同
+unsafe impl<K: Sync, V: Sync> Send for Range<'_, K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for Range<'_, K, V> {}
+unsafe impl<K: Sync, V: Send> Send for RangeMut<'_, K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for RangeMut<'_, K, V> {}
+
+#[cfg(test)]
+mod test {
+    use super::{BTreeMap, Entry, Node};
+    use std::collections::BTreeMap as StdBTreeMap;
+    use std::ops::Bound;
+
+    conformance_tests!(BTreeMap);
+
+    // 检查B树的结构：key有序、节点大小在范围内、所有叶子在同一层，返回元素个数
+    fn check<K: Ord, V>(map: &BTreeMap<K, V>) {
+        fn walk<K: Ord, V>(
+            node: &Node<K, V>,
+            b: usize,
+            is_root: bool,
+            lo: Option<&K>,
+            hi: Option<&K>,
+            depth: usize,
+            leaf_depth: &mut Option<usize>,
+        ) -> usize {
+            assert!(node.keys.len() < 2 * b);
+            assert!(is_root || node.keys.len() >= b - 1);
+            assert!(!node.keys.is_empty());
+            assert_eq!(node.keys.len(), node.vals.len());
+            assert!(node.keys.windows(2).all(|w| w[0] < w[1]));
+            assert!(lo.is_none_or(|lo| lo < &node.keys[0]));
+            assert!(hi.is_none_or(|hi| node.keys.last().unwrap() < hi));
+            if node.is_leaf() {
+                assert_eq!(*leaf_depth.get_or_insert(depth), depth);
+                return node.keys.len();
+            }
+            assert_eq!(node.children.len(), node.keys.len() + 1);
+            let mut count = node.keys.len();
+            for (i, child) in node.children.iter().enumerate() {
+                let lo = if i == 0 { lo } else { Some(&node.keys[i - 1]) };
+                let hi = node.keys.get(i).or(hi);
+                count += walk(child, b, false, lo, hi, depth + 1, leaf_depth);
+            }
+            count
+        }
+
+        match &map.root {
+            None => assert_eq!(map.len, 0),
+            Some(root) => {
+                let count = walk(root, map.b, true, None, None, 0, &mut None);
+                assert_eq!(count, map.len);
+            }
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let mut map = BTreeMap::new();
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.first_key_value(), None);
+
+        assert_eq!(map.insert(2, "b"), None);
+        assert_eq!(map.insert(1, "a"), None);
+        assert_eq!(map.insert(2, "c"), Some("b"));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&2), Some(&"c"));
+        *map.get_mut(&1).unwrap() = "d";
+        assert_eq!(map.first_key_value(), Some((&1, &"d")));
+        assert_eq!(map.last_key_value(), Some((&2, &"c")));
+        assert_eq!(format!("{:?}", map), r#"{1: "d", 2: "c"}"#);
+
+        assert_eq!(map.remove(&1), Some("d"));
+        assert_eq!(map.remove(&2), Some("c"));
+        assert!(map.is_empty());
+        check(&map);
+    }
+
+    #[test]
+    fn min_degree() {
+        for b in 2..6 {
+            let mut map = BTreeMap::with_min_degree(b);
+            assert_eq!(map.min_degree(), b);
+            // 交替从两端插入，覆盖分裂的各种位置
+            for i in 0..500 {
+                let key = if i % 2 == 0 { i } else { 1000 - i };
+                map.insert(key, i);
+                check(&map);
+            }
+            assert_eq!(map.len(), 500);
+            assert!(map.iter().map(|(k, _)| k).is_sorted());
+            for i in (0..1000).step_by(3) {
+                map.remove(&i);
+                check(&map);
+            }
+            for i in 0..1000 {
+                map.remove(&i);
+            }
+            check(&map);
+            assert!(map.is_empty());
+            assert!(map.root.is_none());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn min_degree_too_small() {
+        BTreeMap::<i32, i32>::with_min_degree(1);
+    }
+
+    #[test]
+    fn range() {
+        let map: BTreeMap<i32, i32> = (0..100).map(|i| (i * 2, i)).collect();
+        fn keys<'a>(r: impl Iterator<Item = (&'a i32, &'a i32)>) -> Vec<i32> {
+            r.map(|(&k, _)| k).collect()
+        }
+
+        assert_eq!(keys(map.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(map.range(9..=16)), [10, 12, 14, 16]);
+        assert_eq!(keys(map.range(195..)), [196, 198]);
+        assert_eq!(keys(map.range(..3)), [0, 2]);
+        assert_eq!(keys(map.range(11..12)), []);
+        assert_eq!(keys(map.range(500..)), []);
+        assert_eq!(
+            keys(map.range((Bound::Excluded(10), Bound::Excluded(16)))),
+            [12, 14]
+        );
+        assert_eq!(map.range(..).count(), 100);
+        assert_eq!(keys(map.range(10..16).rev()), [14, 12, 10]);
+
+        // 从两端交替遍历，在中间相遇
+        let mut range = map.range(20..=40);
+        let mut seen = Vec::new();
+        while let Some((&k, _)) = range.next() {
+            seen.push(k);
+            if let Some((&k, _)) = range.next_back() {
+                seen.push(k);
+            }
+        }
+        assert_eq!(range.next(), None);
+        assert_eq!(range.next_back(), None);
+        seen.sort();
+        assert_eq!(seen, (10..=20).map(|i| i * 2).collect::<Vec<_>>());
+
+        let empty: BTreeMap<i32, i32> = BTreeMap::new();
+        assert_eq!(empty.range(1..2).next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn range_backwards() {
+        let map: BTreeMap<i32, i32> = (0..10).map(|i| (i, i)).collect();
+        #[allow(clippy::reversed_empty_ranges)]
+        map.range(5..3).count();
+    }
+
+    #[test]
+    fn range_mut() {
+        let mut map: BTreeMap<String, i32> = (0..50).map(|i| (format!("{:02}", i), i)).collect();
+        for (_, value) in map.range_mut::<str, _>((Bound::Included("10"), Bound::Excluded("20"))) {
+            *value *= 100;
+        }
+        assert_eq!(map.get("09"), Some(&9));
+        assert_eq!(map.get("10"), Some(&1000));
+        assert_eq!(map.get("19"), Some(&1900));
+        assert_eq!(map.get("20"), Some(&20));
+
+        // 两端同时拿到的可变引用指向不同的元素
+        let mut range = map.range_mut::<str, _>(..);
+        let (_, first) = range.next().unwrap();
+        let (_, last) = range.next_back().unwrap();
+        std::mem::swap(first, last);
+        assert_eq!(map.get("00"), Some(&49));
+        assert_eq!(map.get("49"), Some(&0));
+
+        for (_, value) in &mut map {
+            *value = -*value;
+        }
+        assert_eq!(map.iter_mut().len(), 50);
+        assert_eq!(map.iter().map(|(_, v)| *v).max(), Some(0));
+    }
+
+    #[test]
+    fn entry() {
+        let mut map = BTreeMap::with_min_degree(2);
+        for word in "a b c a b a".split(' ') {
+            *map.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(map.get("a"), Some(&3));
+        assert_eq!(map.get("c"), Some(&1));
+
+        map.entry("d").and_modify(|v| *v = 100).or_default();
+        map.entry("c").and_modify(|v| *v += 10).or_default();
+        assert_eq!(map.get("d"), Some(&0));
+        assert_eq!(map.get("c"), Some(&11));
+
+        match map.entry("b") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &"b");
+                assert_eq!(entry.get(), &2);
+                assert_eq!(entry.insert(20), 2);
+                assert_eq!(entry.remove_entry(), ("b", 20));
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match map.entry("e") {
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &"e");
+                *entry.insert(5) += 1;
+            }
+            Entry::Occupied(_) => unreachable!(),
+        }
+        assert_eq!(map.get("e"), Some(&6));
+
+        // 只按第一个字段比较的key，entry返回的是map中原来的key
+        #[derive(Debug)]
+        struct Tagged(i32, &'static str);
+        impl PartialEq for Tagged {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Tagged {}
+        impl PartialOrd for Tagged {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Tagged {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+        let mut tagged = BTreeMap::new();
+        tagged.insert(Tagged(1, "stored"), 1);
+        match tagged.entry(Tagged(1, "lookup")) {
+            Entry::Occupied(entry) => {
+                assert_eq!(entry.key().1, "stored");
+                assert_eq!(entry.remove_entry().0 .1, "stored");
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+
+        // 插入时分裂节点，返回的引用仍然指向新元素
+        let mut map = BTreeMap::with_min_degree(2);
+        for i in 0..100 {
+            *map.entry(i).or_insert_with(|| i * 2) += 1;
+        }
+        check(&map);
+        assert!(map.iter().all(|(&k, &v)| v == k * 2 + 1));
+    }
+
+    #[test]
+    fn split_off_append() {
+        let mut left: BTreeMap<i32, i32> = (0..100).map(|i| (i, i)).collect();
+        let mut right = left.split_off(&60);
+        check(&left);
+        check(&right);
+        assert_eq!(left.len(), 60);
+        assert_eq!(right.first_key_value(), Some((&60, &60)));
+        assert_eq!(left.last_key_value(), Some((&59, &59)));
+
+        // key相同时用被合并进来的value
+        right.insert(0, -1);
+        left.append(&mut right);
+        check(&left);
+        assert!(right.is_empty());
+        assert_eq!(left.len(), 100);
+        assert_eq!(left.get(&0), Some(&-1));
+
+        let mut empty = BTreeMap::with_min_degree(3);
+        empty.append(&mut left);
+        assert_eq!(empty.min_degree(), 3);
+        assert_eq!(empty.len(), 100);
+        check(&empty);
+        assert_eq!(empty.split_off(&1000).len(), 0);
+        assert_eq!(empty.split_off(&-1).len(), 100);
+        assert!(empty.is_empty());
+    }
+
+    // 在每个位置切开，两边的结构都要合法，再合并回去还是原来的内容
+    #[test]
+    fn split_off_every_key() {
+        for b in 2..5 {
+            for n in [0, 1, 2, 10, 57, 300] {
+                let map: BTreeMap<i32, i32> = (0..n).map(|i| (i, -i)).collect();
+                for at in -1..=n {
+                    let mut left = BTreeMap::with_min_degree(b);
+                    left.extend(map.iter().map(|(&k, &v)| (k, v)));
+                    let mut right = left.split_off(&at);
+                    check(&left);
+                    check(&right);
+                    assert!(left.iter().map(|(&k, _)| k).eq(0..at.clamp(0, n)));
+                    assert!(right.iter().map(|(&k, _)| k).eq(at.clamp(0, n)..n));
+                    left.append(&mut right);
+                    check(&left);
+                    assert!(left.iter().eq(map.iter()));
+                }
+            }
+        }
+    }
+
+    // 直接建树时每种元素个数得到的结构都要合法
+    #[test]
+    fn bulk_build() {
+        for b in 2..5 {
+            for n in 0..400 {
+                let mut map = BTreeMap::with_min_degree(b);
+                map.append(&mut (0..n).map(|i| (i, i)).collect());
+                check(&map);
+                assert_eq!(map.len(), n as usize);
+                assert!(map.iter().map(|(&k, _)| k).eq(0..n));
+            }
+        }
+    }
+
+    #[test]
+    fn traits() {
+        let map: BTreeMap<i32, String> = (0..20).rev().map(|i| (i, i.to_string())).collect();
+        let copy = map.clone();
+        assert_eq!(copy, map);
+        assert_eq!(
+            map.iter().rev().map(|(k, _)| *k).collect::<Vec<_>>(),
+            (0..20).rev().collect::<Vec<_>>()
+        );
+        let owned: Vec<_> = copy.into_iter().collect();
+        assert_eq!(
+            owned,
+            (0..20).map(|i| (i, i.to_string())).collect::<Vec<_>>()
+        );
+        assert_ne!(map, BTreeMap::default());
+    }
+
+    // 每个value都只被drop一次
+    #[test]
+    fn drop_values() {
+        use std::rc::Rc;
+
+        let value = Rc::new(());
+        let mut map = BTreeMap::with_min_degree(2);
+        for i in 0..200 {
+            map.insert(i, value.clone());
+        }
+        assert_eq!(Rc::strong_count(&value), 201);
+        for i in 0..100 {
+            map.remove(&i);
+        }
+        assert_eq!(Rc::strong_count(&value), 101);
+        let mut iter = map.into_iter();
+        iter.next();
+        drop(iter);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    // 和std::collections::BTreeMap做同样的随机操作，每一步的结果都必须一致
+    #[test]
+    fn matches_std() {
+        for b in [2, 3, 4, 6] {
+            let mut map = BTreeMap::with_min_degree(b);
+            let mut model = StdBTreeMap::new();
+            let mut state = 0x2545_f491_4f6c_dd1du64 ^ b as u64;
+            let mut rand = move |n: u64| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state % n
+            };
+            for step in 0..10_000u32 {
+                let key = rand(400) as u16;
+                match rand(12) {
+                    0..=2 => assert_eq!(map.insert(key, step), model.insert(key, step)),
+                    3 | 4 => assert_eq!(map.remove(&key), model.remove(&key)),
+                    5 => assert_eq!(map.get(&key), model.get(&key)),
+                    6 => {
+                        let lo = Bound::Included(key);
+                        let hi = match rand(3) {
+                            0 => Bound::Unbounded,
+                            1 => Bound::Included(key + rand(50) as u16),
+                            _ => Bound::Excluded(key + 1 + rand(50) as u16),
+                        };
+                        // 正向、反向、从两端交替
+                        let ours: Vec<_> = map.range((lo, hi)).collect();
+                        let expected: Vec<_> = model.range((lo, hi)).collect();
+                        assert_eq!(ours, expected);
+                        assert!(map.range((lo, hi)).rev().eq(model.range((lo, hi)).rev()));
+                        let mut ours = map.range((lo, hi));
+                        let mut expected = model.range((lo, hi));
+                        loop {
+                            let pair = (ours.next_back(), expected.next_back());
+                            assert_eq!(pair.0, pair.1);
+                            let pair2 = (ours.next(), expected.next());
+                            assert_eq!(pair2.0, pair2.1);
+                            if pair.0.is_none() || pair2.0.is_none() {
+                                break;
+                            }
+                        }
+                    }
+                    7 => {
+                        let hi = key + rand(30) as u16;
+                        for (_, value) in map.range_mut(key..hi) {
+                            *value += 1;
+                        }
+                        for (_, value) in model.range_mut(key..hi) {
+                            *value += 1;
+                        }
+                    }
+                    8 => {
+                        assert_eq!(map.first_key_value(), model.first_key_value());
+                        assert_eq!(map.last_key_value(), model.last_key_value());
+                    }
+                    9 => {
+                        let ours = map.entry(key).and_modify(|v| *v *= 2).or_insert(step);
+                        let expected = model.entry(key).and_modify(|v| *v *= 2).or_insert(step);
+                        assert_eq!(ours, expected);
+                    }
+                    10 => {
+                        if let Entry::Occupied(entry) = map.entry(key) {
+                            assert_eq!(Some(entry.remove()), model.remove(&key));
+                        } else {
+                            assert_eq!(model.get(&key), None);
+                        }
+                    }
+                    _ => {
+                        // 切开之后再合并回去
+                        if rand(20) == 0 {
+                            let mut right = map.split_off(&key);
+                            let mut expected = model.split_off(&key);
+                            assert!(right.iter().eq(expected.iter()));
+                            assert!(map.iter().eq(model.iter()));
+                            check(&right);
+                            map.append(&mut right);
+                            model.append(&mut expected);
+                        }
+                    }
+                }
+                assert_eq!(map.len(), model.len());
+                if step % 500 == 0 {
+                    check(&map);
+                }
+            }
+            check(&map);
+            assert!(map.iter().eq(model.iter()));
+            assert!(map.into_iter().eq(model));
+        }
+    }
+}
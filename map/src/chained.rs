@@ -0,0 +1,474 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash};
+use std::iter::{Chain, FromIterator};
+use std::{mem, slice, vec};
+
+use lists::second::{self, List};
+
+// 拉链法的哈希表，每个桶是一个lists::second::List单链表
+// 扩容不是一次完成的：新桶数组分配好之后，旧桶先留在old里，之后每次写操作搬几个旧桶，
+// 把一次O(n)的rehash分摊到后续的操作上，避免某一次插入特别慢。
+// 扩容期间旧桶没搬走的key都在旧桶里，已经搬走的都在新桶里，所以根据下标就能确定只查哪一个桶。
+pub struct HashMap<K, V, S = RandomState> {
+    // 长度总是0或者2的幂
+    buckets: Vec<Bucket<K, V>>,
+    // 正在搬迁的旧桶，下标小于moved的已经搬空了
+    old: Vec<Bucket<K, V>>,
+    moved: usize,
+    len: usize,
+    hash_builder: S,
+}
+
+type Bucket<K, V> = List<(K, V)>;
+
+// 每次写操作最多搬迁的旧桶数
+const MIGRATE_STEP: usize = 4;
+const MIN_BUCKETS: usize = 8;
+
+// 先遍历还没搬走的旧桶，再遍历新桶
+type Buckets<I> = Chain<I, I>;
+
+pub struct Iter<'a, K, V> {
+    buckets: Buckets<slice::Iter<'a, Bucket<K, V>>>,
+    bucket: Option<second::Iter<'a, (K, V)>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, K, V> {
+    buckets: Buckets<slice::IterMut<'a, Bucket<K, V>>>,
+    bucket: Option<second::IterMut<'a, (K, V)>>,
+    len: usize,
+}
+
+pub struct IntoIter<K, V> {
+    buckets: Buckets<vec::IntoIter<Bucket<K, V>>>,
+    bucket: Option<second::IntoIter<(K, V)>>,
+    len: usize,
+}
+
+fn new_buckets<K, V>(n: usize) -> Vec<Bucket<K, V>> {
+    let mut buckets = Vec::with_capacity(n);
+    buckets.resize_with(n, List::new);
+    buckets
+}
+
+impl<K, V> HashMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        HashMap {
+            buckets: Vec::new(),
+            old: Vec::new(),
+            moved: 0,
+            len: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    // 是否还有旧桶没有搬完
+    pub fn is_rehashing(&self) -> bool {
+        !self.old.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.old = Vec::new();
+        self.moved = 0;
+        self.buckets.iter_mut().for_each(|bucket| *bucket = List::new());
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            buckets: self.old[self.moved..].iter().chain(self.buckets.iter()),
+            bucket: None,
+            len: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            buckets: self.old[self.moved..].iter_mut().chain(self.buckets.iter_mut()),
+            bucket: None,
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
+    }
+
+    // key所在的桶：还没搬走的旧桶，或者新桶
+    fn bucket_of(&self, hash: u64) -> Option<&Bucket<K, V>> {
+        if !self.old.is_empty() {
+            let index = hash as usize & (self.old.len() - 1);
+            if index >= self.moved {
+                return Some(&self.old[index]);
+            }
+        }
+        if self.buckets.is_empty() {
+            return None;
+        }
+        Some(&self.buckets[hash as usize & (self.buckets.len() - 1)])
+    }
+
+    fn bucket_of_mut(&mut self, hash: u64) -> Option<&mut Bucket<K, V>> {
+        if !self.old.is_empty() {
+            let index = hash as usize & (self.old.len() - 1);
+            if index >= self.moved {
+                return Some(&mut self.old[index]);
+            }
+        }
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let index = hash as usize & (self.buckets.len() - 1);
+        Some(&mut self.buckets[index])
+    }
+
+    // 搬迁最多n个旧桶，全部搬完之后释放旧桶数组
+    fn migrate(&mut self, n: usize) {
+        let end = self.moved.saturating_add(n).min(self.old.len());
+        let mask = self.buckets.len().wrapping_sub(1);
+        while self.moved < end {
+            let bucket = mem::take(&mut self.old[self.moved]);
+            for (key, value) in bucket {
+                let index = self.hash(&key) as usize & mask;
+                self.buckets[index].push((key, value));
+            }
+            self.moved += 1;
+        }
+        if self.moved == self.old.len() {
+            self.old = Vec::new();
+            self.moved = 0;
+        }
+    }
+
+    // 平均每个桶超过一个元素时桶数翻倍，旧桶留到之后慢慢搬
+    fn grow_if_needed(&mut self) {
+        if self.len < self.buckets.len() {
+            return;
+        }
+        // 上一次扩容还没搬完，先一次搬完，正常情况下不会走到这里
+        self.migrate(usize::MAX);
+        let n = (self.buckets.len() * 2).max(MIN_BUCKETS);
+        self.old = mem::replace(&mut self.buckets, new_buckets(n));
+        self.moved = 0;
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.bucket_of(self.hash(key))?
+            .iter()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(k, v)| (k, v))
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hash(key);
+        self.bucket_of_mut(hash)?
+            .iter_mut()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // key已经存在时替换value并返回旧值，key保持不变
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.migrate(MIGRATE_STEP);
+        let hash = self.hash(&key);
+        if let Some(slot) = self.get_mut(&key) {
+            return Some(mem::replace(slot, value));
+        }
+        self.grow_if_needed();
+        // 如果对应的旧桶还没搬走，新元素也放进旧桶，保证查找时只需要看一个桶
+        self.bucket_of_mut(hash).unwrap().push((key, value));
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.migrate(MIGRATE_STEP);
+        let hash = self.hash(key);
+        let entry = self
+            .bucket_of_mut(hash)?
+            .remove_first(|(k, _)| k.borrow() == key)?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<K, V, S: Default> Default for HashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Debug, V: Debug, S> Debug for HashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for HashMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for HashMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        // 已经搬空的旧桶遍历时也只是空链表，这里直接整个交出去
+        IntoIter {
+            buckets: mem::take(&mut self.old)
+                .into_iter()
+                .chain(mem::take(&mut self.buckets)),
+            bucket: None,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut HashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// 当前桶遍历完了就换下一个桶
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.bucket.as_mut().and_then(Iterator::next) {
+                self.len -= 1;
+                return Some((key, value));
+            }
+            self.bucket = Some(self.buckets.next()?.iter());
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.bucket.as_mut().and_then(Iterator::next) {
+                self.len -= 1;
+                return Some((&*key, value));
+            }
+            self.bucket = Some(self.buckets.next()?.iter_mut());
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.bucket.as_mut().and_then(Iterator::next) {
+                self.len -= 1;
+                return Some(entry);
+            }
+            self.bucket = Some(self.buckets.next()?.into_iter());
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+#[cfg(test)]
+mod test {
+    use super::{HashMap, MIGRATE_STEP, MIN_BUCKETS};
+    use std::hash::BuildHasher;
+
+    conformance_tests!(HashMap);
+
+    // 每个元素都在它的hash对应的桶里，旧桶里已经搬走的部分是空的
+    fn check_buckets<S: BuildHasher>(map: &HashMap<u32, u32, S>) {
+        let mut count = 0;
+        for (index, bucket) in map.buckets.iter().enumerate() {
+            for (key, _) in bucket {
+                assert_eq!(map.hash(key) as usize & (map.buckets.len() - 1), index);
+            }
+            count += bucket.len();
+        }
+        for (index, bucket) in map.old.iter().enumerate() {
+            if index < map.moved {
+                assert!(bucket.is_empty());
+            }
+            for (key, _) in bucket {
+                assert_eq!(map.hash(key) as usize & (map.old.len() - 1), index);
+            }
+            count += bucket.len();
+        }
+        assert_eq!(count, map.len());
+    }
+
+    #[test]
+    fn incremental_rehash() {
+        let mut map = HashMap::new();
+        for i in 0..MIN_BUCKETS as u32 {
+            map.insert(i, i);
+        }
+        assert!(!map.is_rehashing());
+
+        // 这一次插入触发扩容，旧桶都还没搬
+        map.insert(100, 100);
+        assert!(map.is_rehashing());
+        assert_eq!(map.moved, 0);
+        assert_eq!(map.buckets.len(), MIN_BUCKETS * 2);
+        check_buckets(&map);
+
+        // 扩容期间所有key都能找到，每次写操作最多搬MIGRATE_STEP个桶
+        for key in 0..MIN_BUCKETS as u32 {
+            assert_eq!(map.get(&key), Some(&key));
+        }
+        let before = map.moved;
+        map.insert(101, 101);
+        assert_eq!(map.moved, before + MIGRATE_STEP);
+        check_buckets(&map);
+        assert_eq!(map.remove(&3), Some(3));
+        check_buckets(&map);
+        assert!(!map.is_rehashing());
+        assert_eq!(map.len(), MIN_BUCKETS + 1);
+    }
+
+    #[test]
+    fn rehash_keeps_entries() {
+        let mut map = HashMap::new();
+        for i in 0..10_000 {
+            map.insert(i, i);
+            if i % 97 == 0 {
+                check_buckets(&map);
+                assert_eq!(map.iter().len(), map.len());
+                assert_eq!(map.iter().count(), map.len());
+            }
+        }
+        for i in (0..10_000).step_by(2) {
+            map.remove(&i);
+        }
+        check_buckets(&map);
+        for i in 0..10_000 {
+            assert_eq!(map.contains_key(&i), i % 2 == 1);
+        }
+    }
+
+    #[test]
+    fn iterators() {
+        let mut map: HashMap<u32, u32> = (0..20).map(|i| (i, i)).collect();
+        // 在扩容中途遍历
+        assert!(map.is_rehashing());
+        for (_, value) in &mut map {
+            *value *= 2;
+        }
+        let mut pairs: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        pairs.sort();
+        assert_eq!(pairs, (0..20).map(|i| (i, i * 2)).collect::<Vec<_>>());
+
+        let mut owned: Vec<_> = map.into_iter().collect();
+        owned.sort();
+        assert_eq!(owned, pairs);
+
+        let mut map: HashMap<&str, i32> = HashMap::default();
+        map.insert("a", 1);
+        assert_eq!(format!("{:?}", map), r#"{"a": 1}"#);
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get("a"), None);
+        map.insert("b", 2);
+        assert_eq!(map.get("b"), Some(&2));
+    }
+}
@@ -0,0 +1,409 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::{thread, vec};
+
+use crate::robinhood::HashMap;
+
+// 可以在多个线程之间共享的map，放进Arc里交给ThreadPool的各个job使用
+// key按hash分到N个分片，每个分片是一把RwLock保护的哈希表，
+// 单个key的操作只锁一个分片，不同分片上的读写互不影响。
+// 需要整体一致的操作（snapshot、clear）按下标顺序锁住所有分片，
+// 单key操作只持有一把锁，所以不会死锁。
+pub struct ShardedMap<K, V, S = RandomState> {
+    shards: Box<[RwLock<HashMap<K, V, S>>]>,
+    hash_builder: S,
+}
+
+// 持有分片的读锁，期间其他线程不能修改这个分片
+pub struct ReadGuard<'a, K, V, S> {
+    _guard: RwLockReadGuard<'a, HashMap<K, V, S>>,
+    value: NonNull<V>,
+}
+
+// snapshot时复制出来的所有元素
+pub struct Snapshot<K, V>(vec::IntoIter<(K, V)>);
+
+// 默认每个CPU核4个分片，线程数不多时锁冲突很少
+fn default_shards() -> usize {
+    thread::available_parallelism().map_or(1, usize::from) * 4
+}
+
+impl<K, V> ShardedMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_shards(default_shards())
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        Self::with_shards_and_hasher(shards, RandomState::new())
+    }
+}
+
+impl<K, V, S: Clone> ShardedMap<K, V, S> {
+    pub fn with_shards_and_hasher(shards: usize, hash_builder: S) -> Self {
+        assert!(shards > 0, "ShardedMap needs at least one shard");
+        ShardedMap {
+            shards: (0..shards)
+                .map(|_| RwLock::new(HashMap::with_hasher(hash_builder.clone())))
+                .collect(),
+            hash_builder,
+        }
+    }
+}
+
+impl<K, V, S> ShardedMap<K, V, S> {
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    // 闭包panic不会破坏哈希表本身的结构，所以忽略锁的poison状态
+    fn read(&self, shard: usize) -> RwLockReadGuard<'_, HashMap<K, V, S>> {
+        self.shards[shard]
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self, shard: usize) -> RwLockWriteGuard<'_, HashMap<K, V, S>> {
+        self.shards[shard]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // 逐个分片累加，并发修改时只是某一时刻附近的值
+    pub fn len(&self) -> usize {
+        (0..self.shards.len())
+            .map(|shard| self.read(shard).len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..self.shards.len()).all(|shard| self.read(shard).is_empty())
+    }
+
+    // 先锁住所有分片再清空，其他线程不会看到只清空了一部分的map
+    pub fn clear(&self) {
+        let mut guards: Vec<_> = (0..self.shards.len())
+            .map(|shard| self.write(shard))
+            .collect();
+        for guard in &mut guards {
+            guard.clear();
+        }
+    }
+
+    // 同时持有所有分片的读锁，复制出某一时刻完整的内容，
+    // 之后的遍历不再持有锁，也不受其他线程修改的影响
+    pub fn snapshot(&self) -> Snapshot<K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let guards: Vec<_> = (0..self.shards.len())
+            .map(|shard| self.read(shard))
+            .collect();
+        let mut entries = Vec::with_capacity(guards.iter().map(|guard| guard.len()).sum());
+        for guard in &guards {
+            entries.extend(guard.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Snapshot(entries.into_iter())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> ShardedMap<K, V, S> {
+    // 分片内部的哈希表用hash的低位选桶，这里用高位选分片，避免同一个分片里的key挤在一起
+    fn shard_of<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        (self.hash_builder.hash_one(key) >> 32) as usize % self.shards.len()
+    }
+
+    // 返回value的克隆，不持有锁
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.read(self.shard_of(key)).get(key).cloned()
+    }
+
+    // 不克隆value，返回的guard持有分片的读锁，用完要尽快drop
+    pub fn get_ref<Q>(&self, key: &Q) -> Option<ReadGuard<'_, K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let guard = self.read(self.shard_of(key));
+        let value = NonNull::from(guard.get(key)?);
+        Some(ReadGuard {
+            _guard: guard,
+            value,
+        })
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.read(self.shard_of(key)).contains_key(key)
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let shard = self.shard_of(&key);
+        self.write(shard).insert(key, value)
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.write(self.shard_of(key)).remove(key)
+    }
+
+    // 在分片的写锁里修改value，整个读-改-写对其他线程是原子的
+    // key不存在时返回None，f里不能再访问这个map，否则可能死锁
+    pub fn update<Q, R, F>(&self, key: &Q, f: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&mut V) -> R,
+    {
+        self.write(self.shard_of(key)).get_mut(key).map(f)
+    }
+}
+
+impl<K, V, S: Default + Clone> Default for ShardedMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_shards_and_hasher(default_shards(), S::default())
+    }
+}
+
+impl<K, V, S> Deref for ReadGuard<'_, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        // 读锁还没释放，value一直有效
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<K, V> Iterator for Snapshot<K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Snapshot<K, V> {}
+
+#[cfg(test)]
+mod test {
+    use super::ShardedMap;
+    use multithreading_server::ThreadPool;
+    use std::collections::HashMap as StdHashMap;
+    use std::sync::{mpsc, Arc};
+
+    #[test]
+    fn basics() {
+        let map = ShardedMap::with_shards(4);
+        assert_eq!(map.shard_count(), 4);
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+        assert!(map.get_ref(&1).is_none());
+        assert_eq!(map.update(&1, |v: &mut String| v.push('!')), None);
+
+        assert_eq!(map.insert(1, "a".to_string()), None);
+        assert_eq!(map.insert(2, "b".to_string()), None);
+        assert_eq!(map.insert(1, "c".to_string()), Some("a".to_string()));
+        assert_eq!(map.len(), 2);
+        assert!(map.contains_key(&2));
+        assert_eq!(map.get(&1), Some("c".to_string()));
+        assert_eq!(map.get_ref(&2).as_deref().map(String::as_str), Some("b"));
+
+        assert_eq!(
+            map.update(&1, |v| {
+                v.push('!');
+                v.len()
+            }),
+            Some(2)
+        );
+        assert_eq!(map.get(&1).as_deref(), Some("c!"));
+
+        let mut entries: Vec<_> = map.snapshot().collect();
+        entries.sort();
+        assert_eq!(entries, [(1, "c!".to_string()), (2, "b".to_string())]);
+
+        assert_eq!(map.remove(&1), Some("c!".to_string()));
+        assert_eq!(map.remove(&1), None);
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.snapshot().len(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_shards() {
+        ShardedMap::<i32, i32>::with_shards(0);
+    }
+
+    // update里的闭包panic之后，map还能继续使用
+    #[test]
+    fn poisoned_shard() {
+        let map = Arc::new(ShardedMap::with_shards(1));
+        map.insert(1, 1);
+        let shared = map.clone();
+        let result = std::thread::spawn(move || {
+            shared.update(&1, |_| panic!("boom"));
+        })
+        .join();
+        assert!(result.is_err());
+        assert_eq!(map.update(&1, |v| *v += 1), Some(()));
+        assert_eq!(map.get(&1), Some(2));
+    }
+
+    // 多个job同时插入不相交的key
+    #[test]
+    fn concurrent_inserts() {
+        let map = Arc::new(ShardedMap::new());
+        let pool = ThreadPool::new(4);
+        for job in 0..16u32 {
+            let map = Arc::clone(&map);
+            pool.execute(move || {
+                for i in 0..1000 {
+                    assert_eq!(map.insert(job * 1000 + i, job), None);
+                }
+            });
+        }
+        // drop会等所有job执行完
+        drop(pool);
+
+        assert_eq!(map.len(), 16_000);
+        let mut entries: Vec<_> = map.snapshot().collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            (0..16_000).map(|k| (k, k / 1000)).collect::<Vec<_>>()
+        );
+    }
+
+    // 多个job同时对同一批key做读-改-写，不能丢失任何一次更新
+    #[test]
+    fn concurrent_updates() {
+        let map = Arc::new(ShardedMap::with_shards(8));
+        for key in 0..100 {
+            map.insert(key, 0u64);
+        }
+        let pool = ThreadPool::new(8);
+        for job in 0..8 {
+            let map = Arc::clone(&map);
+            pool.execute(move || {
+                for i in 0..10_000 {
+                    map.update(&((i + job) % 100), |v| *v += 1).unwrap();
+                }
+            });
+        }
+        drop(pool);
+
+        assert_eq!(map.snapshot().map(|(_, v)| v).sum::<u64>(), 80_000);
+        assert!((0..100).all(|key| map.get(&key) == Some(800)));
+    }
+
+    // 每个写job按顺序插入自己的key，一致的快照里每个job的key一定是从0开始的连续一段。
+    // 如果快照是逐个分片读的，就可能看到后插入的key而漏掉先插入的key
+    #[test]
+    fn consistent_snapshots() {
+        const WRITERS: u32 = 4;
+        const KEYS: u32 = 5_000;
+
+        let map = Arc::new(ShardedMap::with_shards(16));
+        let pool = ThreadPool::new(8);
+        for writer in 0..WRITERS {
+            let map = Arc::clone(&map);
+            pool.execute(move || {
+                for i in 0..KEYS {
+                    map.insert((writer, i), ());
+                }
+            });
+        }
+        for _ in 0..4 {
+            let map = Arc::clone(&map);
+            pool.execute(move || {
+                for _ in 0..50 {
+                    let mut seen = vec![Vec::new(); WRITERS as usize];
+                    for ((writer, i), ()) in map.snapshot() {
+                        seen[writer as usize].push(i);
+                    }
+                    for keys in &mut seen {
+                        keys.sort();
+                        assert!(keys.iter().copied().eq(0..keys.len() as u32));
+                    }
+                }
+            });
+        }
+        drop(pool);
+        assert_eq!(map.len(), (WRITERS * KEYS) as usize);
+    }
+
+    // 随机混合操作：每个job只修改自己的key并维护一份本地模型，同时读别的job的key，
+    // 全部结束后合并所有模型，必须和map的内容一致
+    #[test]
+    fn mixed_stress() {
+        const JOBS: u64 = 8;
+
+        let map = Arc::new(ShardedMap::new());
+        let pool = ThreadPool::new(4);
+        let (sender, receiver) = mpsc::channel();
+        for job in 0..JOBS {
+            let map = Arc::clone(&map);
+            let sender = sender.clone();
+            pool.execute(move || {
+                let mut model = StdHashMap::new();
+                let mut state = 0x9e37_79b9_7f4a_7c15u64 + job;
+                for step in 0..20_000u64 {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    let key = (state >> 32) % 256 * JOBS + job;
+                    match state % 6 {
+                        0 | 1 => assert_eq!(map.insert(key, step), model.insert(key, step)),
+                        2 => assert_eq!(map.remove(&key), model.remove(&key)),
+                        3 => assert_eq!(
+                            map.update(&key, |v| {
+                                *v += 1;
+                                *v
+                            }),
+                            model.get_mut(&key).map(|v| {
+                                *v += 1;
+                                *v
+                            })
+                        ),
+                        4 => assert_eq!(map.get(&key), model.get(&key).copied()),
+                        _ => {
+                            // 别的job的key随时可能变化，只要求读的时候不出错
+                            let other = key / JOBS * JOBS + (job + 1) % JOBS;
+                            if let Some(value) = map.get_ref(&other) {
+                                assert!(*value < 40_000);
+                            }
+                        }
+                    }
+                }
+                sender.send(model).unwrap();
+            });
+        }
+        drop(sender);
+        drop(pool);
+
+        let mut expected: Vec<_> = receiver.iter().flatten().collect();
+        let mut entries: Vec<_> = map.snapshot().collect();
+        expected.sort();
+        entries.sort();
+        assert_eq!(entries, expected);
+        assert_eq!(map.len(), expected.len());
+    }
+}
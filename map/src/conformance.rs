@@ -0,0 +1,276 @@
+// 所有map实现共用的一致性测试：每个实现在自己的测试模块里调用conformance_tests!，
+// 跑同一组用例，行为必须和std::collections::HashMap一致
+use std::collections::HashMap as StdHashMap;
+use std::hash::{BuildHasher, Hash};
+use std::rc::Rc;
+
+// 测试用到的最小接口
+pub(crate) trait TestMap<K, V>: Default {
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    fn get(&self, key: &K) -> Option<&V>;
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn len(&self) -> usize;
+    fn pairs(&self) -> Vec<(&K, &V)>;
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> TestMap<K, V> for crate::robinhood::HashMap<K, V, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> TestMap<K, V> for crate::chained::HashMap<K, V, S> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K: Ord, V> TestMap<K, V> for crate::btree::BTreeMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+// 用std自己跑一遍，保证用例本身是对的
+impl<K: Hash + Eq, V> TestMap<K, V> for StdHashMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+// 排好序的键值对，方便和期望结果比较
+fn sorted<K: Ord + Clone, V: Clone, M: TestMap<K, V>>(map: &M) -> Vec<(K, V)> {
+    let mut pairs: Vec<_> = map
+        .pairs()
+        .into_iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    pairs
+}
+
+pub(crate) fn basics<M: TestMap<u32, &'static str>>() {
+    let mut map = M::default();
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.get(&1), None);
+    assert_eq!(map.remove(&1), None);
+
+    assert_eq!(map.insert(1, "a"), None);
+    assert_eq!(map.insert(2, "b"), None);
+    assert_eq!(map.insert(1, "c"), Some("a"));
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&1), Some(&"c"));
+    assert_eq!(map.get(&2), Some(&"b"));
+
+    *map.get_mut(&2).unwrap() = "d";
+    assert_eq!(map.get(&2), Some(&"d"));
+    assert_eq!(map.get_mut(&3), None);
+
+    assert_eq!(map.remove(&1), Some("c"));
+    assert_eq!(map.remove(&1), None);
+    assert_eq!(map.len(), 1);
+    assert_eq!(sorted(&map), [(2, "d")]);
+}
+
+// 插入足够多的元素触发多次扩容，再全部删除，最后重新插入
+pub(crate) fn grow_and_shrink<M: TestMap<u32, u32>>() {
+    let mut map = M::default();
+    for i in 0..5_000 {
+        assert_eq!(map.insert(i, i * 2), None);
+        assert_eq!(map.len(), i as usize + 1);
+    }
+    for i in 0..5_000 {
+        assert_eq!(map.get(&i), Some(&(i * 2)));
+    }
+    assert_eq!(map.get(&5_000), None);
+    assert_eq!(sorted(&map), (0..5_000).map(|i| (i, i * 2)).collect::<Vec<_>>());
+
+    for i in (0..5_000).rev() {
+        assert_eq!(map.remove(&i), Some(i * 2));
+    }
+    assert_eq!(map.len(), 0);
+    assert!(map.pairs().is_empty());
+
+    for i in 0..100 {
+        map.insert(i, i);
+    }
+    assert_eq!(sorted(&map), (0..100).map(|i| (i, i)).collect::<Vec<_>>());
+}
+
+pub(crate) fn string_keys<M: TestMap<String, usize>>() {
+    let mut map = M::default();
+    let words = ["alpha", "beta", "gamma", "delta", "epsilon"];
+    for (i, word) in words.iter().enumerate() {
+        map.insert(word.to_string(), i);
+    }
+    for (i, word) in words.iter().enumerate() {
+        assert_eq!(map.get(&word.to_string()), Some(&i));
+    }
+    assert_eq!(map.remove(&"gamma".to_string()), Some(2));
+    assert_eq!(map.get(&"gamma".to_string()), None);
+    assert_eq!(map.len(), 4);
+}
+
+// 每个value都只被drop一次：被替换、被删除、随map一起drop
+pub(crate) fn drop_values<M: TestMap<u32, Rc<()>>>() {
+    let value = Rc::new(());
+    let mut map = M::default();
+    for i in 0..200 {
+        map.insert(i, value.clone());
+    }
+    assert_eq!(Rc::strong_count(&value), 201);
+    drop(map.insert(0, value.clone()));
+    assert_eq!(Rc::strong_count(&value), 201);
+    for i in 0..100 {
+        drop(map.remove(&i));
+    }
+    assert_eq!(Rc::strong_count(&value), 101);
+    drop(map);
+    assert_eq!(Rc::strong_count(&value), 1);
+}
+
+// 和std::collections::HashMap做同样的随机操作，结果必须一致
+pub(crate) fn matches_std<M: TestMap<u16, u32>>() {
+    let mut map = M::default();
+    let mut model = StdHashMap::new();
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    for step in 0..20_000u32 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let key = (state >> 32) as u16 % 512;
+        match state % 5 {
+            0 | 1 => assert_eq!(map.insert(key, step), model.insert(key, step)),
+            2 => assert_eq!(map.remove(&key), model.remove(&key)),
+            3 => assert_eq!(map.get_mut(&key).map(|v| *v), model.get_mut(&key).map(|v| *v)),
+            _ => assert_eq!(map.get(&key), model.get(&key)),
+        }
+        assert_eq!(map.len(), model.len());
+    }
+    let mut expected: Vec<_> = model.into_iter().collect();
+    expected.sort();
+    assert_eq!(sorted(&map), expected);
+}
+
+// 在调用处展开成一组#[test]，$map是只接收K, V两个类型参数的map类型
+macro_rules! conformance_tests {
+    ($map:ident) => {
+        mod conformance {
+            use super::$map;
+            use crate::conformance;
+
+            #[test]
+            fn basics() {
+                conformance::basics::<$map<_, _>>();
+            }
+
+            #[test]
+            fn grow_and_shrink() {
+                conformance::grow_and_shrink::<$map<_, _>>();
+            }
+
+            #[test]
+            fn string_keys() {
+                conformance::string_keys::<$map<_, _>>();
+            }
+
+            #[test]
+            fn drop_values() {
+                conformance::drop_values::<$map<_, _>>();
+            }
+
+            #[test]
+            fn matches_std() {
+                conformance::matches_std::<$map<_, _>>();
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    conformance_tests!(HashMap);
+}
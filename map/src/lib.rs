@@ -0,0 +1,12 @@
+// 自己实现的几种map，和std::collections里的同名类型接口保持一致
+#[cfg(test)]
+#[macro_use]
+mod conformance;
+
+pub mod btree;
+pub mod chained;
+pub mod concurrent;
+pub mod lru;
+pub mod robinhood;
+
+pub use robinhood::HashMap;
@@ -0,0 +1,429 @@
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ptr::{self, NonNull};
+
+use lists::linkedlist::{self, Handle, LinkedList};
+
+use crate::HashMap;
+
+// O(1)的LRU缓存：链表按使用时间排序，最近使用的在头部，最久没用的在尾部；
+// 哈希表从key索引到链表节点的句柄，查到之后直接把节点移到头部或者删除。
+// 链表节点的地址在删除之前不会改变，所以哈希表里不再复制一份key，只保存指向节点中key的指针。
+// key放进索引之后，只通过Handle::as_ptr按字段访问节点，不再创建覆盖整个Entry的&mut，
+// 否则按Stacked Borrows的规则，索引里的key指针会失效。
+pub struct LruCache<K, V> {
+    // 每个句柄都指向list中还没有被删除的节点
+    index: HashMap<KeyRef<K>, Handle<Entry<K, V>>>,
+    list: LinkedList<Entry<K, V>>,
+    // 所有元素权重之和的上限，默认每个元素的权重都是1，也就是元素个数的上限
+    capacity: usize,
+    weight: usize,
+    weigher: Weigher<K, V>,
+    on_evict: Option<OnEvict<K, V>>,
+}
+
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize>;
+type OnEvict<K, V> = Box<dyn FnMut(K, V)>;
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    // 放入时计算的权重，删除时直接减掉，不需要再调用weigher
+    weight: usize,
+}
+
+// 指向链表节点中的key，按key本身计算hash和比较
+struct KeyRef<K>(NonNull<K>);
+
+pub struct Iter<'a, K, V> {
+    inner: linkedlist::Iter<'a, Entry<K, V>>,
+}
+
+impl<K> KeyRef<K> {
+    fn new(key: &K) -> Self {
+        KeyRef(NonNull::from(key))
+    }
+
+    // 指向链表节点中的key，调用者保证节点还在链表中
+    unsafe fn from_handle<V>(handle: Handle<Entry<K, V>>) -> Self {
+        KeyRef(NonNull::new_unchecked(ptr::addr_of_mut!((*handle.as_ptr()).key)))
+    }
+
+    fn get(&self) -> &K {
+        // 哈希表里的KeyRef指向的节点都还在链表中，查找时临时构造的KeyRef指向调用者的key
+        unsafe { self.0.as_ref() }
+    }
+}
+
+impl<K: Hash> Hash for KeyRef<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state);
+    }
+}
+
+impl<K: PartialEq> PartialEq for KeyRef<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<K: Eq> Eq for KeyRef<K> {}
+
+impl<K: Hash + Eq, V> LruCache<K, V> {
+    // 最多保存capacity个元素
+    pub fn new(capacity: usize) -> Self {
+        Self::with_weigher(capacity, |_, _| 1)
+    }
+
+    // 按权重计算容量：每个元素的权重由weigher决定，总权重不超过capacity
+    pub fn with_weigher<F>(capacity: usize, weigher: F) -> Self
+    where
+        F: Fn(&K, &V) -> usize + 'static,
+    {
+        LruCache {
+            index: HashMap::new(),
+            list: LinkedList::new(),
+            capacity,
+            weight: 0,
+            weigher: Box::new(weigher),
+            on_evict: None,
+        }
+    }
+
+    // 因为超出容量被淘汰的元素会交给回调，pop_lru和remove主动取走的元素不会
+    pub fn on_evict<F: FnMut(K, V) + 'static>(&mut self, f: F) {
+        self.on_evict = Some(Box::new(f));
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // 当前所有元素的权重之和，不设置weigher时和len相同
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    fn handle(&self, key: &K) -> Option<Handle<Entry<K, V>>> {
+        self.index.get(&KeyRef::new(key)).copied()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.handle(key).is_some()
+    }
+
+    // 返回value并把它标记为最近使用
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    // 和get一样会标记为最近使用，权重仍然是放入时计算的值
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let handle = self.handle(key)?;
+        unsafe {
+            self.list.move_to_front_unchecked(handle);
+            Some(&mut *ptr::addr_of_mut!((*handle.as_ptr()).value))
+        }
+    }
+
+    // 只读取，不改变使用顺序
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let handle = self.handle(key)?;
+        unsafe { Some(&*ptr::addr_of!((*handle.as_ptr()).value)) }
+    }
+
+    // 下一个会被淘汰的元素
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.list.back().map(|entry| (&entry.key, &entry.value))
+    }
+
+    // 放入或者更新元素并标记为最近使用，key已经存在时返回旧的value
+    // 之后如果超出容量，就从最久没用的元素开始淘汰，权重超过容量的新元素自己也会被淘汰
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        let weight = (self.weigher)(&key, &value);
+        let old = match self.handle(&key) {
+            Some(handle) => unsafe {
+                self.list.move_to_front_unchecked(handle);
+                let entry = handle.as_ptr();
+                self.weight = self.weight - (*entry).weight + weight;
+                (*entry).weight = weight;
+                Some(mem::replace(&mut *ptr::addr_of_mut!((*entry).value), value))
+            },
+            None => {
+                self.list.push_front(Entry { key, value, weight });
+                let handle = self.list.front_handle().unwrap();
+                self.index.insert(unsafe { KeyRef::from_handle(handle) }, handle);
+                self.weight += weight;
+                None
+            }
+        };
+        self.evict();
+        old
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let handle = self.handle(key)?;
+        Some(self.remove_handle(handle).1)
+    }
+
+    // 取出最久没用的元素
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let handle = self.list.back_handle()?;
+        Some(self.remove_handle(handle))
+    }
+
+    // 修改容量，变小时立即淘汰多出来的元素
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.list.clear();
+        self.weight = 0;
+    }
+
+    // 从最近使用到最久没用
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.list.iter(),
+        }
+    }
+
+    fn remove_handle(&mut self, handle: Handle<Entry<K, V>>) -> (K, V) {
+        unsafe {
+            // 先从索引中删除，这时KeyRef指向的key还在
+            self.index.remove(&KeyRef::from_handle(handle));
+            let entry = self.list.remove_unchecked(handle);
+            self.weight -= entry.weight;
+            (entry.key, entry.value)
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.weight > self.capacity {
+            // 总权重大于0，链表一定不为空
+            let (key, value) = self.pop_lru().unwrap();
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, value);
+            }
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.list.iter().map(|entry| (&entry.key, &entry.value)))
+            .finish()
+    }
+}
+
+impl<'a, K: Hash + Eq, V> IntoIterator for &'a LruCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (&entry.key, &entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+// 反向就是从最久没用到最近使用
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+mod test {
+    use super::LruCache;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn keys<V>(cache: &LruCache<i32, V>) -> Vec<i32> {
+        cache.iter().map(|(&k, _)| k).collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut cache = LruCache::new(3);
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.pop_lru(), None);
+
+        assert_eq!(cache.put(1, "a"), None);
+        assert_eq!(cache.put(2, "b"), None);
+        assert_eq!(cache.put(3, "c"), None);
+        assert_eq!(keys(&cache), [3, 2, 1]);
+
+        // get会把元素移到最前面，peek不会
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.peek(&2), Some(&"b"));
+        assert_eq!(keys(&cache), [1, 3, 2]);
+        assert_eq!(cache.peek_lru(), Some((&2, &"b")));
+
+        // 超出容量，淘汰最久没用的2
+        assert_eq!(cache.put(4, "d"), None);
+        assert_eq!(keys(&cache), [4, 1, 3]);
+        assert!(!cache.contains(&2));
+
+        // 更新已有的key也算一次使用
+        assert_eq!(cache.put(3, "e"), Some("c"));
+        assert_eq!(keys(&cache), [3, 4, 1]);
+        *cache.get_mut(&1).unwrap() = "f";
+        assert_eq!(format!("{:?}", cache), r#"{1: "f", 3: "e", 4: "d"}"#);
+
+        assert_eq!(cache.remove(&3), Some("e"));
+        assert_eq!(cache.remove(&3), None);
+        assert_eq!(cache.pop_lru(), Some((4, "d")));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.iter().rev().count(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.weight(), 0);
+        cache.put(5, "g");
+        assert_eq!(keys(&cache), [5]);
+    }
+
+    #[test]
+    fn eviction_callback() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let mut cache = LruCache::new(2);
+        let log = evicted.clone();
+        cache.on_evict(move |key, value| log.borrow_mut().push((key, value)));
+
+        cache.put(1, 10);
+        cache.put(2, 20);
+        cache.get(&1);
+        cache.put(3, 30);
+        assert_eq!(*evicted.borrow(), [(2, 20)]);
+
+        // 主动取走的元素不经过回调
+        assert_eq!(cache.pop_lru(), Some((1, 10)));
+        assert_eq!(cache.remove(&3), Some(30));
+        assert_eq!(evicted.borrow().len(), 1);
+
+        // 缩小容量时淘汰多出来的元素，从最久没用的开始
+        for i in 0..2 {
+            cache.put(i, i);
+        }
+        cache.resize(5);
+        for i in 2..5 {
+            cache.put(i, i);
+        }
+        assert!(evicted.borrow().len() == 1);
+        cache.resize(2);
+        assert_eq!(keys(&cache), [4, 3]);
+        assert_eq!(evicted.borrow()[1..], [(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(cache.capacity(), 2);
+
+        cache.resize(0);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn weighted() {
+        // 权重是字符串的长度，总长度不超过10
+        let mut cache = LruCache::with_weigher(10, |_, value: &String| value.len());
+        cache.put(1, "aaaa".to_string());
+        cache.put(2, "bbbb".to_string());
+        assert_eq!(cache.weight(), 8);
+
+        // 加入之后总长度是12，淘汰1
+        cache.put(3, "cccc".to_string());
+        assert_eq!(keys(&cache), [3, 2]);
+        assert_eq!(cache.weight(), 8);
+
+        // 更新时重新计算权重
+        cache.put(2, "b".to_string());
+        assert_eq!(cache.weight(), 5);
+        cache.put(4, "ddddd".to_string());
+        assert_eq!(keys(&cache), [4, 2, 3]);
+        assert_eq!(cache.weight(), 10);
+
+        // 一个元素就超过了容量，它自己也会被淘汰
+        cache.put(5, "e".repeat(11));
+        assert!(cache.is_empty());
+        assert_eq!(cache.weight(), 0);
+    }
+
+    // 和一个用Vec实现的O(n)模型比较，头部是最近使用的
+    #[test]
+    fn matches_model() {
+        let mut cache = LruCache::new(16);
+        let mut model: Vec<(u8, u32)> = Vec::new();
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for step in 0..20_000u32 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let key = (state >> 40) as u8 % 32;
+            let pos = model.iter().position(|&(k, _)| k == key);
+            match state % 4 {
+                0 | 1 => {
+                    let old = pos.map(|i| model.remove(i).1);
+                    model.insert(0, (key, step));
+                    model.truncate(16);
+                    assert_eq!(cache.put(key, step), old);
+                }
+                2 => {
+                    let expected = pos.map(|i| {
+                        let entry = model.remove(i);
+                        model.insert(0, entry);
+                        entry.1
+                    });
+                    assert_eq!(cache.get(&key).copied(), expected);
+                }
+                _ => {
+                    let expected = pos.map(|i| model.remove(i).1);
+                    assert_eq!(cache.remove(&key), expected);
+                }
+            }
+            assert_eq!(cache.len(), model.len());
+            assert_eq!(cache.weight(), model.len());
+        }
+        let items: Vec<_> = cache.iter().map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(items, model);
+    }
+
+    // 每个value都只被drop一次
+    #[test]
+    fn drop_values() {
+        let value = Rc::new(());
+        let mut cache = LruCache::new(10);
+        for i in 0..20 {
+            cache.put(i, value.clone());
+        }
+        assert_eq!(Rc::strong_count(&value), 11);
+        cache.put(15, value.clone());
+        cache.remove(&16);
+        assert_eq!(Rc::strong_count(&value), 10);
+        drop(cache);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}
@@ -1,3 +1,122 @@
+use std::collections::HashMap as StdHashMap;
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use map::HashMap;
+
+// 两种实现都需要的最小接口，benchmark对它们跑完全相同的操作
+trait Map {
+    fn name() -> &'static str;
+    fn new() -> Self;
+    fn insert(&mut self, key: u64, value: u64);
+    fn get(&self, key: u64) -> Option<u64>;
+    fn remove(&mut self, key: u64) -> Option<u64>;
+}
+
+impl Map for HashMap<u64, u64> {
+    fn name() -> &'static str {
+        "map::HashMap"
+    }
+
+    fn new() -> Self {
+        HashMap::new()
+    }
+
+    fn insert(&mut self, key: u64, value: u64) {
+        HashMap::insert(self, key, value);
+    }
+
+    fn get(&self, key: u64) -> Option<u64> {
+        HashMap::get(self, &key).copied()
+    }
+
+    fn remove(&mut self, key: u64) -> Option<u64> {
+        HashMap::remove(self, &key)
+    }
+}
+
+impl Map for StdHashMap<u64, u64> {
+    fn name() -> &'static str {
+        "std HashMap"
+    }
+
+    fn new() -> Self {
+        StdHashMap::new()
+    }
+
+    fn insert(&mut self, key: u64, value: u64) {
+        StdHashMap::insert(self, key, value);
+    }
+
+    fn get(&self, key: u64) -> Option<u64> {
+        StdHashMap::get(self, &key).copied()
+    }
+
+    fn remove(&mut self, key: u64) -> Option<u64> {
+        StdHashMap::remove(self, &key)
+    }
+}
+
+// splitmix64，生成分布均匀但可以复现的key
+fn key(i: u64) -> u64 {
+    let mut z = i.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn time<F: FnOnce()>(f: F) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn bench<M: Map>(n: u64) {
+    let mut map = M::new();
+    let insert = time(|| {
+        for i in 0..n {
+            map.insert(key(i), i);
+        }
+    });
+    let hit = time(|| {
+        for i in 0..n {
+            black_box(map.get(key(i)));
+        }
+    });
+    // 不存在的key，查找一直探测到空桶为止
+    let miss = time(|| {
+        for i in n..2 * n {
+            black_box(map.get(key(i)));
+        }
+    });
+    let remove = time(|| {
+        for i in 0..n {
+            black_box(map.remove(key(i)));
+        }
+    });
+
+    println!(
+        "{:<14}{:>12.2?}{:>12.2?}{:>12.2?}{:>12.2?}",
+        M::name(),
+        insert,
+        hit,
+        miss,
+        remove
+    );
+}
+
 fn main() {
-    println!("Hello, world!");
+    let n = match env::args().nth(1) {
+        Some(arg) => arg.parse().expect("usage: map [N]"),
+        None => 1_000_000,
+    };
+
+    println!("{} keys", n);
+    println!(
+        "{:<14}{:>12}{:>12}{:>12}{:>12}",
+        "", "insert", "get hit", "get miss", "remove"
+    );
+    bench::<HashMap<u64, u64>>(n);
+    bench::<StdHashMap<u64, u64>>(n);
 }
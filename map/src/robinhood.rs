@@ -0,0 +1,897 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::{mem, slice, vec};
+
+// 开放寻址 + Robin Hood 探测的哈希表
+// 每个元素都有一个理想位置 hash & mask，实际位置和理想位置的差叫探测距离。
+// 插入时如果遇到探测距离比自己小的元素（"富人"），就和它交换，带着它继续往后找位置（"劫富济贫"），
+// 这样整张表的探测距离比较平均，查找时遇到探测距离比自己小的元素就可以提前结束。
+pub struct HashMap<K, V, S = RandomState> {
+    // 长度总是0或者2的幂
+    buckets: Vec<Option<Bucket<K, V>>>,
+    len: usize,
+    hash_builder: S,
+}
+
+// 保存完整的hash，扩容时不需要重新计算，比较key之前也可以先比较hash
+#[derive(Clone)]
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+// 负载因子上限 7/8，Robin Hood 的探测距离在高负载下也比较短
+const LOAD_NUM: usize = 7;
+const LOAD_DEN: usize = 8;
+const MIN_BUCKETS: usize = 8;
+
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut HashMap<K, V, S>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut HashMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+pub struct Iter<'a, K, V> {
+    buckets: slice::Iter<'a, Option<Bucket<K, V>>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, K, V> {
+    buckets: slice::IterMut<'a, Option<Bucket<K, V>>>,
+    len: usize,
+}
+
+pub struct IntoIter<K, V> {
+    buckets: vec::IntoIter<Option<Bucket<K, V>>>,
+    len: usize,
+}
+
+// 桶在drain期间移出map，map暂时是一张没有桶的空表，
+// 所以即使Drain被mem::forget，map也仍然是合法的，只是丢掉了元素和已分配的桶
+pub struct Drain<'a, K, V> {
+    buckets: Vec<Option<Bucket<K, V>>>,
+    // Drop时把清空的桶放回这里
+    home: &'a mut Vec<Option<Bucket<K, V>>>,
+    next: usize,
+    len: usize,
+}
+
+// 能放下capacity个元素所需要的桶数
+fn buckets_for(capacity: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    capacity
+        .checked_mul(LOAD_DEN)
+        .expect("capacity overflow")
+        .div_ceil(LOAD_NUM)
+        .next_power_of_two()
+        .max(MIN_BUCKETS)
+}
+
+impl<K, V> HashMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    // 不分配内存，第一次插入时才分配桶
+    pub fn with_hasher(hash_builder: S) -> Self {
+        HashMap {
+            buckets: Vec::new(),
+            len: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut map = Self::with_hasher(hash_builder);
+        map.resize(buckets_for(capacity));
+        map
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 不需要扩容就能放下的元素个数
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() / LOAD_DEN * LOAD_NUM
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    // 保留已经分配的桶
+    pub fn clear(&mut self) {
+        self.drain();
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            buckets: self.buckets.iter(),
+            len: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            buckets: self.buckets.iter_mut(),
+            len: self.len,
+        }
+    }
+
+    // 取出所有元素，保留已经分配的桶；Drain没有遍历完就被drop时，剩下的元素也会被删除
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain {
+            buckets: mem::take(&mut self.buckets),
+            home: &mut self.buckets,
+            next: 0,
+            len: mem::replace(&mut self.len, 0),
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        if needed > self.capacity() {
+            self.resize(buckets_for(needed));
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        let target = buckets_for(self.len);
+        if target < self.buckets.len() {
+            self.resize(target);
+        }
+    }
+
+    fn mask(&self) -> usize {
+        self.buckets.len() - 1
+    }
+
+    // index处的元素离它的理想位置有多远
+    fn probe_distance(&self, hash: u64, index: usize) -> usize {
+        index.wrapping_sub(hash as usize) & self.mask()
+    }
+
+    // 换一组新的桶，hash已经保存在桶里，直接重新放置，不需要K: Hash
+    fn resize(&mut self, new_buckets: usize) {
+        debug_assert!(new_buckets == 0 || new_buckets.is_power_of_two());
+        debug_assert!(new_buckets / LOAD_DEN * LOAD_NUM >= self.len);
+        let mut buckets = Vec::with_capacity(new_buckets);
+        buckets.resize_with(new_buckets, || None);
+        let old = mem::replace(&mut self.buckets, buckets);
+        for bucket in old.into_iter().flatten() {
+            self.place(bucket);
+        }
+    }
+
+    // 放入一个确定不在表中的元素，返回它最终所在的位置
+    // 调用前必须保证至少还有一个空桶
+    fn place(&mut self, mut bucket: Bucket<K, V>) -> usize {
+        let mask = self.mask();
+        let mut index = bucket.hash as usize & mask;
+        let mut dist = 0;
+        // 第一次交换时新元素就落在那里，之后带着走的是被换出来的元素
+        let mut placed = None;
+        loop {
+            let slot = &mut self.buckets[index];
+            match slot {
+                None => {
+                    *slot = Some(bucket);
+                    return placed.unwrap_or(index);
+                }
+                Some(cur) => {
+                    let cur_dist = index.wrapping_sub(cur.hash as usize) & mask;
+                    // 劫富济贫：当前元素离理想位置更近，就把位置让出来，带着它继续往后找
+                    if cur_dist < dist {
+                        mem::swap(cur, &mut bucket);
+                        placed.get_or_insert(index);
+                        dist = cur_dist;
+                    }
+                }
+            }
+            index = (index + 1) & mask;
+            dist += 1;
+        }
+    }
+
+    fn insert_new(&mut self, hash: u64, key: K, value: V) -> usize {
+        self.reserve(1);
+        self.len += 1;
+        self.place(Bucket { hash, key, value })
+    }
+
+    // 向后移位删除：把后面不在理想位置上的元素依次往前挪一格，填上空出来的位置，不需要墓碑
+    fn remove_index(&mut self, index: usize) -> Bucket<K, V> {
+        let mask = self.mask();
+        let removed = self.buckets[index].take().unwrap();
+        self.len -= 1;
+        let mut hole = index;
+        loop {
+            let next = (hole + 1) & mask;
+            match &self.buckets[next] {
+                Some(bucket) if self.probe_distance(bucket.hash, next) > 0 => {}
+                _ => break,
+            }
+            self.buckets[hole] = self.buckets[next].take();
+            hole = next;
+        }
+        removed
+    }
+
+    fn bucket(&self, index: usize) -> &Bucket<K, V> {
+        self.buckets[index].as_ref().unwrap()
+    }
+
+    fn bucket_mut(&mut self, index: usize) -> &mut Bucket<K, V> {
+        self.buckets[index].as_mut().unwrap()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.len == 0 {
+            return None;
+        }
+        let mask = self.mask();
+        let mut index = hash as usize & mask;
+        let mut dist = 0;
+        // 负载因子小于1，总能遇到空桶，循环一定会结束
+        loop {
+            let bucket = self.buckets[index].as_ref()?;
+            // 如果要找的key在表中，它不可能排在一个比它更"富"的元素后面
+            if self.probe_distance(bucket.hash, index) < dist {
+                return None;
+            }
+            if bucket.hash == hash && bucket.key.borrow() == key {
+                return Some(index);
+            }
+            index = (index + 1) & mask;
+            dist += 1;
+        }
+    }
+
+    fn find_key<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hash(key), key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket = self.bucket(self.find_key(key)?);
+        Some((&bucket.key, &bucket.value))
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find_key(key)?;
+        Some(&mut self.bucket_mut(index).value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_key(key).is_some()
+    }
+
+    // key已经存在时替换value并返回旧值，key保持不变
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hash(&key);
+        match self.find(hash, &key) {
+            Some(index) => Some(mem::replace(&mut self.bucket_mut(index).value, value)),
+            None => {
+                self.insert_new(hash, key, value);
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket = self.remove_index(self.find_key(key)?);
+        Some((bucket.key, bucket.value))
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash = self.hash(&key);
+        match self.find(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                hash,
+                key,
+            }),
+        }
+    }
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.map.bucket(self.index).key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.bucket(self.index).value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.bucket_mut(self.index).value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.bucket_mut(self.index).value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        let bucket = self.map.remove_index(self.index);
+        (bucket.key, bucket.value)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    // 可能触发扩容，位置要在放入之后才能确定
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.map.insert_new(self.hash, self.key, value);
+        &mut self.map.bucket_mut(index).value
+    }
+}
+
+impl<K, V, S: Default> Default for HashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> Clone for HashMap<K, V, S> {
+    fn clone(&self) -> Self {
+        HashMap {
+            buckets: self.buckets.clone(),
+            len: self.len,
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug, S> Debug for HashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// 和顺序无关，只要键值对的集合相同就相等
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for HashMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for HashMap<K, V, S> {}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for HashMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for HashMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            buckets: self.buckets.into_iter(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut HashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self
+            .buckets
+            .find_map(|slot| slot.as_ref().map(|b| (&b.key, &b.value)))?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self
+            .buckets
+            .find_map(|slot| slot.as_mut().map(|b| (&b.key, &mut b.value)))?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self
+            .buckets
+            .find_map(|slot| slot.map(|b| (b.key, b.value)))?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, bucket) = self.buckets[self.next..]
+            .iter_mut()
+            .enumerate()
+            .find_map(|(i, slot)| slot.take().map(|b| (i, b)))?;
+        self.next += index + 1;
+        self.len -= 1;
+        Some((bucket.key, bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+impl<K, V> Drop for Drain<'_, K, V> {
+    fn drop(&mut self) {
+        for slot in &mut self.buckets[self.next..] {
+            *slot = None;
+        }
+        *self.home = mem::take(&mut self.buckets);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Entry, HashMap};
+    use std::collections::HashMap as StdHashMap;
+    use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+    // 只用低几位的hash，让很多key落在同一个理想位置上，专门测试探测和移位
+    #[derive(Default)]
+    struct CollidingHasher(u64);
+
+    impl Hasher for CollidingHasher {
+        fn finish(&self) -> u64 {
+            self.0 % 5
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for &byte in bytes {
+                self.0 = self.0.wrapping_mul(31).wrapping_add(byte as u64);
+            }
+        }
+    }
+
+    type Colliding = BuildHasherDefault<CollidingHasher>;
+
+    conformance_tests!(HashMap);
+
+    // 检查Robin Hood的不变量：空桶之后的元素一定在理想位置上，相邻元素的探测距离最多增加1
+    fn check_invariants<K, V, S: BuildHasher>(map: &HashMap<K, V, S>) {
+        let n = map.buckets.len();
+        assert!(n == 0 || n.is_power_of_two());
+        assert!(map.len <= map.capacity());
+        assert_eq!(map.buckets.iter().flatten().count(), map.len);
+        for index in 0..n {
+            let Some(bucket) = &map.buckets[index] else {
+                continue;
+            };
+            let dist = map.probe_distance(bucket.hash, index);
+            let prev = (index + n - 1) % n;
+            match &map.buckets[prev] {
+                None => assert_eq!(dist, 0),
+                Some(p) => assert!(dist <= map.probe_distance(p.hash, prev) + 1),
+            }
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let mut map = HashMap::new();
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove(&1), None);
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), 0);
+
+        assert_eq!(map.insert(1, "a"), None);
+        assert_eq!(map.insert(2, "b"), None);
+        assert_eq!(map.insert(1, "c"), Some("a"));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&"c"));
+        assert!(map.contains_key(&2));
+
+        *map.get_mut(&2).unwrap() = "d";
+        assert_eq!(map.get_key_value(&2), Some((&2, &"d")));
+
+        assert_eq!(map.remove(&1), Some("c"));
+        assert_eq!(map.remove(&1), None);
+        assert_eq!(map.len(), 1);
+        check_invariants(&map);
+    }
+
+    #[test]
+    fn borrowed_keys() {
+        let mut map: HashMap<String, usize> = HashMap::new();
+        map.insert("one".to_string(), 1);
+        map.insert("two".to_string(), 2);
+        assert_eq!(map.get("one"), Some(&1));
+        assert_eq!(map.remove_entry("two"), Some(("two".to_string(), 2)));
+        assert!(!map.contains_key("two"));
+    }
+
+    #[test]
+    fn entry() {
+        let mut map: HashMap<&str, i32> = HashMap::new();
+        *map.entry("a").or_insert(1) += 10;
+        *map.entry("a").or_insert(1) += 10;
+        assert_eq!(map.get("a"), Some(&21));
+
+        map.entry("b").and_modify(|v| *v = 100).or_default();
+        map.entry("b").and_modify(|v| *v += 1).or_default();
+        assert_eq!(map.get("b"), Some(&1));
+
+        match map.entry("a") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &"a");
+                assert_eq!(entry.insert(5), 21);
+                assert_eq!(entry.remove(), 5);
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match map.entry("c") {
+            Entry::Vacant(entry) => assert_eq!(entry.into_key(), "c"),
+            Entry::Occupied(_) => unreachable!(),
+        }
+        assert_eq!(map.len(), 1);
+
+        // 插入时触发扩容，返回的引用仍然指向新元素
+        let mut map = HashMap::new();
+        for i in 0..100 {
+            *map.entry(i).or_insert_with(|| i * 2) += 1;
+        }
+        for i in 0..100 {
+            assert_eq!(map.get(&i), Some(&(i * 2 + 1)));
+        }
+        check_invariants(&map);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut map: HashMap<i32, i32> = (0..10).map(|i| (i, i)).collect();
+        let mut pairs: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        pairs.sort();
+        assert_eq!(pairs, (0..10).map(|i| (i, i)).collect::<Vec<_>>());
+        assert_eq!(map.iter().len(), 10);
+
+        for (_, value) in &mut map {
+            *value *= 10;
+        }
+        assert_eq!(map.get(&3), Some(&30));
+
+        let mut drained: Vec<_> = map.drain().collect();
+        drained.sort();
+        assert_eq!(drained, (0..10).map(|i| (i, i * 10)).collect::<Vec<_>>());
+        assert!(map.is_empty());
+        assert!(map.capacity() >= 10);
+        check_invariants(&map);
+
+        // 没有遍历完的Drain也会清空表
+        map.extend((0..10).map(|i| (i, i)));
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), 10);
+        drain.next();
+        drop(drain);
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+        check_invariants(&map);
+
+        // Drain被泄漏时map变成没有桶的空表，之后仍然可以正常使用
+        map.extend((0..10).map(|i| (i, i)));
+        std::mem::forget(map.drain());
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), 0);
+        map.extend((0..100).map(|i| (i, i)));
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&99), Some(&99));
+        assert_eq!(map.get(&100), None);
+        check_invariants(&map);
+        map.clear();
+
+        map.extend((0..5).map(|i| (i, -i)));
+        let mut owned: Vec<_> = map.into_iter().collect();
+        owned.sort();
+        assert_eq!(owned, vec![(0, 0), (1, -1), (2, -2), (3, -3), (4, -4)]);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut map: HashMap<i32, ()> = HashMap::with_capacity(100);
+        assert!(map.capacity() >= 100);
+        let buckets = map.buckets.len();
+        for i in 0..100 {
+            map.insert(i, ());
+        }
+        assert_eq!(map.buckets.len(), buckets);
+
+        map.reserve(1000);
+        assert!(map.capacity() >= 1100);
+        check_invariants(&map);
+
+        for i in 10..100 {
+            map.remove(&i);
+        }
+        map.shrink_to_fit();
+        assert!(map.capacity() >= 10);
+        assert!(map.buckets.len() <= 16);
+        for i in 0..10 {
+            assert!(map.contains_key(&i));
+        }
+        check_invariants(&map);
+
+        map.clear();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 0);
+        assert_eq!(map.get(&1), None);
+        map.insert(1, ());
+        assert!(map.contains_key(&1));
+    }
+
+    #[test]
+    fn traits() {
+        let map: HashMap<i32, String> = (0..20).map(|i| (i, i.to_string())).collect();
+        let copy = map.clone();
+        assert_eq!(copy, map);
+
+        // 插入顺序不同也相等
+        let reversed: HashMap<i32, String> = (0..20).rev().map(|i| (i, i.to_string())).collect();
+        assert_eq!(reversed, map);
+
+        let mut other = map.clone();
+        other.insert(0, "zero".to_string());
+        assert_ne!(other, map);
+
+        let single: HashMap<i32, i32> = std::iter::once((1, 2)).collect();
+        assert_eq!(format!("{:?}", single), "{1: 2}");
+    }
+
+    #[test]
+    fn collisions() {
+        let mut map: HashMap<u32, u32, Colliding> = HashMap::default();
+        for i in 0..200 {
+            map.insert(i, i);
+            check_invariants(&map);
+        }
+        for i in (0..200).step_by(3) {
+            assert_eq!(map.remove(&i), Some(i));
+            check_invariants(&map);
+        }
+        for i in 0..200 {
+            let expected = if i % 3 == 0 { None } else { Some(&i) };
+            assert_eq!(map.get(&i), expected);
+        }
+    }
+
+    // 和std::collections::HashMap做同样的随机操作，结果必须一致
+    #[test]
+    fn matches_std() {
+        fn run<S: BuildHasher + Default>() {
+            let mut map: HashMap<u16, u32, S> = HashMap::default();
+            let mut model = StdHashMap::new();
+            let mut state = 0x2545_f491_4f6c_dd1du64;
+            for step in 0..20_000u32 {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let key = (state >> 32) as u16 % 512;
+                match state % 4 {
+                    0 | 1 => assert_eq!(map.insert(key, step), model.insert(key, step)),
+                    2 => assert_eq!(map.remove(&key), model.remove(&key)),
+                    _ => assert_eq!(map.get(&key), model.get(&key)),
+                }
+                assert_eq!(map.len(), model.len());
+                if step % 1000 == 0 {
+                    check_invariants(&map);
+                }
+            }
+            check_invariants(&map);
+            let mut pairs: Vec<_> = map.into_iter().collect();
+            let mut expected: Vec<_> = model.into_iter().collect();
+            pairs.sort();
+            expected.sort();
+            assert_eq!(pairs, expected);
+        }
+
+        run::<std::collections::hash_map::RandomState>();
+        run::<Colliding>();
+    }
+
+    // 每个元素都只被drop一次
+    #[test]
+    fn drop_values() {
+        use std::rc::Rc;
+
+        let value = Rc::new(());
+        let mut map = HashMap::new();
+        for i in 0..50 {
+            map.insert(i, value.clone());
+        }
+        assert_eq!(Rc::strong_count(&value), 51);
+        map.insert(0, value.clone());
+        assert_eq!(Rc::strong_count(&value), 51);
+        map.remove(&1);
+        map.drain().take(5).for_each(drop);
+        assert_eq!(Rc::strong_count(&value), 1);
+        for i in 0..50 {
+            map.insert(i, value.clone());
+        }
+        drop(map);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}